@@ -0,0 +1,322 @@
+//! Arena-backed element type for Decision Diagrams
+use {
+    crate::{
+        cache::ComputedTable,
+        node::write_graph,
+        types::{DecisionDiagram, DecisionDiagramNode},
+    },
+    std::{
+        cell::RefCell,
+        collections::{HashMap, HashSet},
+        io,
+        marker::PhantomData,
+    },
+};
+
+/// A 32-bit handle to a vertex stored in the arena of the current thread.
+/// The handles 0 and 1 are the terminals for false and true.
+/// Since vertices are hash-consed and never released, a child always has
+/// a smaller handle than its parents.
+///
+/// A handle is meaningful only in the thread which made it, so it's neither
+/// `Send` nor `Sync`. The arena keeps every vertex until the thread exits;
+/// run each batch of work in its own thread to release the memory.
+///```compile_fail
+/// use ddir::arena::ArenaNode;
+/// use ddir::types::DecisionDiagramNode;
+///
+/// let t = ArenaNode::new_constant(true);
+/// std::thread::spawn(move || t.is_constant());
+///```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ArenaNode(u32, PhantomData<*const ()>);
+
+impl ArenaNode {
+    const fn handle(index: u32) -> Self {
+        ArenaNode(index, PhantomData)
+    }
+}
+
+/// the var index stored in the terminal slots
+const TERMINAL: u32 = u32::MAX;
+
+thread_local! {
+    static ARENA: RefCell<Arena> = RefCell::new(Arena::default());
+    static COMPUTED_TABLE: RefCell<ComputedTable<ArenaNode>> =
+        RefCell::new(ComputedTable::default());
+}
+
+#[derive(Debug)]
+struct Arena {
+    /// `(var_index, low, high)` triples indexed by handle
+    nodes: Vec<(u32, ArenaNode, ArenaNode)>,
+    /// the unique table from a triple to its handle
+    unique_table: HashMap<(u32, ArenaNode, ArenaNode), ArenaNode>,
+}
+
+impl Default for Arena {
+    fn default() -> Self {
+        Arena {
+            nodes: vec![
+                (TERMINAL, ArenaNode::handle(0), ArenaNode::handle(0)),
+                (TERMINAL, ArenaNode::handle(1), ArenaNode::handle(1)),
+            ],
+            unique_table: HashMap::new(),
+        }
+    }
+}
+
+impl Arena {
+    fn get_or_insert(&mut self, var_index: usize, low: ArenaNode, high: ArenaNode) -> ArenaNode {
+        let vi = u32::try_from(var_index)
+            .ok()
+            .filter(|vi| *vi != TERMINAL)
+            .expect("var index overflow");
+        let key = (vi, low, high);
+        if let Some(n) = self.unique_table.get(&key) {
+            return *n;
+        }
+        let n = ArenaNode::handle(u32::try_from(self.nodes.len()).expect("arena overflow"));
+        self.nodes.push(key);
+        self.unique_table.insert(key, n);
+        n
+    }
+    /// return whether each handle up to `root` is reachable from `root`.
+    /// Since a child has a smaller handle than its parents, a sweep downward
+    /// from `root` marks all of them.
+    fn marks(&self, root: ArenaNode) -> Vec<bool> {
+        let mut marked = vec![false; root.0 as usize + 1];
+        marked[root.0 as usize] = true;
+        for i in (2..marked.len()).rev() {
+            if marked[i] {
+                let (_, low, high) = self.nodes[i];
+                marked[low.0 as usize] = true;
+                marked[high.0 as usize] = true;
+            }
+        }
+        marked
+    }
+    /// return all handles reachable from `root` in ascending order,
+    /// in which every node comes after its children.
+    fn reachable(&self, root: ArenaNode) -> Vec<ArenaNode> {
+        self.marks(root)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, m)| *m)
+            .map(|(i, _)| ArenaNode::handle(i as u32))
+            .collect()
+    }
+    /// return the value of `root` evaluated upward from the terminals, in which
+    /// the value of a non-terminal is `f` of the values of its children.
+    fn evaluate<T: Clone + Default>(
+        &self,
+        root: ArenaNode,
+        terminals: [T; 2],
+        f: impl Fn(&T, &T) -> T,
+    ) -> T {
+        let marked = self.marks(root);
+        let mut values: Vec<T> = vec![T::default(); marked.len().max(2)];
+        values[..2].clone_from_slice(&terminals);
+        for i in (2..marked.len()).filter(|i| marked[*i]) {
+            let (_, low, high) = self.nodes[i];
+            values[i] = f(&values[low.0 as usize], &values[high.0 as usize]);
+        }
+        values.swap_remove(root.0 as usize)
+    }
+}
+
+/// return the number of vertices, including the terminals, in the arena of this thread.
+///```
+/// use ddir::arena::{self, ArenaNode};
+/// use ddir::types::DecisionDiagramNode;
+///
+/// let f = ArenaNode::new_constant(false);
+/// let t = ArenaNode::new_constant(true);
+/// assert_eq!(arena::arena_size(), 2);
+/// let n = ArenaNode::new_var(1, f, t);
+/// assert_eq!(n, ArenaNode::new_var(1, f, t));
+/// assert_eq!(arena::arena_size(), 3);
+///```
+pub fn arena_size() -> usize {
+    ARENA.with(|a| a.borrow().nodes.len())
+}
+
+impl DecisionDiagram<ArenaNode> for ArenaNode {
+    /// returns the number of nodes under self and self itself.
+    ///```
+    /// use ddir::arena::ArenaNode;
+    /// use ddir::types::{DecisionDiagram, DecisionDiagramNode};
+    ///
+    /// let f = ArenaNode::new_constant(false);
+    /// assert_eq!(f.len(), 1);
+    /// let n = ArenaNode::new_var(2, f, f);
+    /// assert_eq!(n.len(), 2);
+    /// let k = ArenaNode::new_var(1, n, f);
+    /// assert_eq!(k.len(), 3);
+    ///```
+    fn len(&self) -> usize {
+        ARENA.with(|a| a.borrow().reachable(*self).len())
+    }
+    fn all_nodes(&self) -> HashSet<ArenaNode> {
+        ARENA.with(|a| a.borrow().reachable(*self).into_iter().collect())
+    }
+    fn write_as_gv(&self, sink: impl io::Write) -> io::Result<()> {
+        write_graph(self, sink)
+    }
+    fn satisfy_one(&self) -> bool {
+        ARENA.with(|a| a.borrow().evaluate(*self, [false, true], |l, h| *l || *h))
+    }
+    fn satisfy_all(&self) -> usize {
+        ARENA.with(|a| a.borrow().evaluate(*self, [0, 1], |l, h| l + h))
+    }
+}
+
+impl DecisionDiagramNode for ArenaNode {
+    /// returns the terminal node.
+    fn new_constant(b: bool) -> ArenaNode {
+        ArenaNode::handle(b as u32)
+    }
+    /// returns the unique non-terminal node which has `var_index`, `low` and `high`.
+    fn new_var(var_index: usize, low: ArenaNode, high: ArenaNode) -> ArenaNode {
+        ARENA.with(|a| a.borrow_mut().get_or_insert(var_index, low, high))
+    }
+    fn is_constant(&self) -> Option<bool> {
+        (self.0 < 2).then_some(self.0 == 1)
+    }
+    fn unified_key(&self) -> usize {
+        self.var_index().map_or(self.0 as usize, |vi| vi + 2)
+    }
+    fn var_index(&self) -> Option<usize> {
+        if self.0 < 2 {
+            return None;
+        }
+        Some(ARENA.with(|a| a.borrow().nodes[self.0 as usize].0) as usize)
+    }
+    fn low(&self) -> Option<ArenaNode> {
+        if self.0 < 2 {
+            return None;
+        }
+        Some(ARENA.with(|a| a.borrow().nodes[self.0 as usize].1))
+    }
+    fn high(&self) -> Option<ArenaNode> {
+        if self.0 < 2 {
+            return None;
+        }
+        Some(ARENA.with(|a| a.borrow().nodes[self.0 as usize].2))
+    }
+    /// rebuilds the nodes in ascending order of handles, in which the children
+    /// come first, with a table indexed by handle.
+    fn rebuild(&self, new_node: &impl Fn(usize, Self, Self) -> Self) -> Self {
+        // copy the nodes out, since `new_node` adds nodes to the arena
+        let nodes = ARENA.with(|a| {
+            let arena = a.borrow();
+            arena
+                .reachable(*self)
+                .into_iter()
+                .filter(|n| 1 < n.0)
+                .map(|n| (n, arena.nodes[n.0 as usize]))
+                .collect::<Vec<_>>()
+        });
+        let mut rebuilt: Vec<ArenaNode> = (0..=self.0).map(ArenaNode::handle).collect();
+        for (n, (vi, low, high)) in nodes {
+            rebuilt[n.0 as usize] = new_node(
+                vi as usize,
+                rebuilt[low.0 as usize],
+                rebuilt[high.0 as usize],
+            );
+        }
+        rebuilt[self.0 as usize]
+    }
+    fn with_computed_table<R>(f: impl FnOnce(&mut ComputedTable<ArenaNode>) -> R) -> R {
+        COMPUTED_TABLE.with(|t| f(&mut t.borrow_mut()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            bdd::BDD,
+            node::{Node, example},
+            types::{BinOp, ReducedDecisionDiagram},
+            zdd::ZDD,
+        },
+    };
+
+    fn convert(node: &Node) -> ArenaNode {
+        match node.is_constant() {
+            Some(b) => ArenaNode::new_constant(b),
+            None => ArenaNode::new_var(
+                node.var_index().unwrap(),
+                convert(&node.low().unwrap()),
+                convert(&node.high().unwrap()),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_satisfy() {
+        let f = ArenaNode::new_constant(false);
+        let t = ArenaNode::new_constant(true);
+        assert!(!f.satisfy_one());
+        assert_eq!(f.satisfy_all(), 0);
+        assert!(t.satisfy_one());
+        assert_eq!(t.satisfy_all(), 1);
+        let ind = convert(&example::independent_set());
+        assert!(ind.satisfy_one());
+        assert_eq!(ind.satisfy_all(), 18);
+        assert_eq!(ind.len(), example::independent_set().len());
+    }
+    #[test]
+    fn test_diagrams() {
+        for tree in [
+            example::independent_set(),
+            example::kernels(),
+            example::majority(),
+        ] {
+            let arena = convert(&tree);
+            assert_eq!(
+                BDD::new_from(arena).len(),
+                BDD::new_from(tree.clone()).len()
+            );
+            assert_eq!(
+                ZDD::new_from(arena).len(),
+                ZDD::new_from(tree.clone()).len()
+            );
+            assert_eq!(
+                ZDD::new_from(arena).satisfy_all(),
+                ZDD::new_from(tree.clone()).satisfy_all()
+            );
+            // the arena-specific reduction agrees with the generic one
+            let mut sets = ZDD::new_from(arena).sets().collect::<Vec<_>>();
+            let mut expected = ZDD::new_from(tree.clone()).sets().collect::<Vec<_>>();
+            sets.sort();
+            expected.sort();
+            assert_eq!(sets, expected);
+            assert_eq!(
+                BDD::new_from(arena).count_models(1..=6),
+                BDD::new_from(tree).count_models(1..=6)
+            );
+        }
+        let f = ArenaNode::new_constant(false);
+        let t = ArenaNode::new_constant(true);
+        assert!(!f.satisfy_one() && t.satisfy_one());
+        assert_eq!((f.satisfy_all(), t.satisfy_all()), (0, 1));
+        assert_eq!(BDD::new_from(ArenaNode::new_var(3, t, t)), BDD::new_from(t));
+        let x1x3 = BDD::new_from(convert(&example::x1x3()));
+        let x2x3 = BDD::new_from(convert(&example::x2x3()));
+        let or = x1x3.apply(BinOp::Or, &x2x3);
+        assert_eq!(or, x2x3.apply(BinOp::Or, &x1x3));
+        assert_eq!(or.len(), 5);
+        assert_eq!(x1x3.negate().satisfy_all(), 1);
+        assert_eq!(x1x3.negate().negate(), x1x3);
+        let x1x2x4 = BDD::new_from(convert(&example::x1x2x4()));
+        assert_eq!(
+            x1x2x4.compose(&x2x3, 2).len(),
+            BDD::new_from(example::x1x2x4())
+                .compose(&BDD::new_from(example::x2x3()), 2)
+                .len()
+        );
+    }
+}
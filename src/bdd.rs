@@ -1,9 +1,14 @@
 //! Binary Decision Diagram
 use {
     crate::{
-        node::{Node, Vertex},
+        cache::{Operation, operand_order},
+        count::{Count, count_paths},
+        iter::{Assignments, Cubes},
+        node::{find_path, preserves_order, relabel},
+        spec::{DdSpec, build},
         types::{
-            BooleanOperator, DecisionDiagram, DecisionDiagramNode, Indexer, ReducedDecisionDiagram,
+            Assignment, BinOp, DecisionDiagram, DecisionDiagramNode, ReducedDecisionDiagram,
+            Residual,
         },
     },
     itertools::Itertools,
@@ -20,19 +25,608 @@ pub struct BDD<N: DecisionDiagramNode> {
     phantom: PhantomData<()>,
 }
 
-impl BDD<Node> {
-    pub fn new_from(graph: Node) -> Self {
+impl<N: DecisionDiagram<N> + DecisionDiagramNode> BDD<N> {
+    pub fn new_from(graph: N) -> Self {
         let mut bdd = BDD {
-            graph: graph.clone(),
+            graph,
             ..Default::default()
         };
         bdd.reduce();
         bdd
     }
+    /// return the BDD specified by `spec`, which is built top-down without
+    /// the decision tree. See [`DdSpec`].
+    pub fn from_spec(spec: &impl DdSpec) -> Self {
+        BDD {
+            graph: build(spec, BDD::new_node),
+            ..Default::default()
+        }
+    }
+    /// return the node for `var_index` after applying the elimination rule of BDD.
+    fn new_node(var_index: usize, low: N, high: N) -> N {
+        if low == high {
+            low
+        } else {
+            N::new_var(var_index, low, high)
+        }
+    }
+    /// return an assignment satisfying self, in which the vars not on the path
+    /// are omitted as don't-cares. It takes time linear in the size of the diagram.
+    ///```
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::example;
+    ///
+    /// let x2x3 = BDD::new_from(example::x2x3());
+    /// let model = x2x3.satisfy_one_model().unwrap();
+    /// assert_eq!(model.len(), 2);
+    /// assert!(model[&2] && model[&3]);
+    /// assert_eq!(x2x3.and(&x2x3.negate()).satisfy_one_model(), None);
+    ///```
+    pub fn satisfy_one_model(&self) -> Option<Assignment> {
+        find_path(&self.graph).map(|path| path.into_iter().collect())
+    }
+    /// return a lazy iterator over the cubes, the partial assignments on the
+    /// paths to the true terminal. The cubes are disjoint, and cover all models.
+    ///```
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::example;
+    ///
+    /// let major = BDD::new_from(example::majority());
+    /// assert_eq!(major.cubes().count(), 3);
+    /// assert!(major.cubes().all(|cube| cube.len() == 2 || cube.len() == 3));
+    ///```
+    pub fn cubes(&self) -> Cubes<N> {
+        Cubes::new(&self.graph)
+    }
+    /// return a lazy iterator over the models, the full assignments to the vars
+    /// in `universe` which satisfy self. All vars in the diagram must be in `universe`.
+    pub fn assignments(&self, universe: impl IntoIterator<Item = usize>) -> Assignments<N> {
+        let vars = universe.into_iter().sorted().dedup().collect::<Vec<_>>();
+        Assignments::new(&self.graph, vars)
+    }
+    /// return the number of paths from the root to the true terminal.
+    /// It's less than the number of models if some vars are eliminated as redundant.
+    pub fn path_count(&self) -> usize {
+        self.graph.satisfy_all()
+    }
+    /// return the number of assignments to the vars in `universe` which satisfy self.
+    /// An edge skipping `k` levels stands for `2^k` assignments.
+    /// All vars in the diagram must be in `universe`.
+    ///```
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::example;
+    ///
+    /// let major = BDD::new_from(example::majority());
+    /// assert_eq!(major.path_count(), 3);
+    /// assert_eq!(major.count_models(1..=3), 4);
+    /// assert_eq!(major.count_models(1..=4), 8);
+    ///```
+    pub fn count_models(&self, universe: impl IntoIterator<Item = usize>) -> usize {
+        self.count_models_as(universe)
+    }
+    /// return the number of models in `universe` as a `C`.
+    /// Use [`BigUint`] for the exact count on more than 128 vars.
+    ///```
+    /// use ddir::bdd::BDD;
+    /// use ddir::count::BigUint;
+    /// use ddir::node::{example, Node};
+    /// use ddir::types::DecisionDiagramNode;
+    ///
+    /// let t = BDD::new_from(Node::new_constant(true));
+    /// let n: BigUint = t.count_models_as(0..200);
+    /// assert_eq!(n.to_string(), "1606938044258990275541962092341162602522202993782792835301376");
+    /// let x2x3 = BDD::new_from(example::x2x3());
+    /// assert_eq!(x2x3.count_models_as::<u128>(0..100), 1 << 98);
+    /// assert_eq!(x2x3.count_models_as::<f64>(0..200), 2f64.powi(198));
+    ///```
+    pub fn count_models_as<C: Count>(&self, universe: impl IntoIterator<Item = usize>) -> C {
+        let vars = universe.into_iter().sorted().dedup().collect::<Vec<_>>();
+        let level = |n: &N| {
+            n.var_index().map_or(vars.len(), |vi| {
+                vars.binary_search(&vi).expect("a var out of the universe")
+            })
+        };
+        let gap = |parent: &N, child: &N| level(child) - level(parent) - 1;
+        count_paths::<N, C>(&self.graph, &gap).shl(level(&self.graph))
+    }
+    /// return the cofactor of self in which `var_index` is fixed to `value`.
+    ///```
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::example;
+    ///
+    /// let major = BDD::new_from(example::majority());
+    /// let x2x3 = BDD::new_from(example::x2x3());
+    /// assert_eq!(major.restrict(1, false), x2x3);
+    ///```
+    pub fn restrict(&self, var_index: usize, value: bool) -> Self {
+        self.restrict_cube(&Assignment::from([(var_index, value)]))
+    }
+    /// return the cofactor of self in which the vars in `cube` are fixed.
+    pub fn restrict_cube(&self, cube: &Assignment) -> Self {
+        let c = BDD::cube_node(cube.iter().map(|(vi, b)| (*vi, *b)));
+        BDD {
+            graph: BDD::restrict_node(&self.graph, &c),
+            ..Default::default()
+        }
+    }
+    /// return the conjunction of `literals`
+    fn cube_node(literals: impl Iterator<Item = (usize, bool)>) -> N {
+        literals
+            .sorted_by(|a, b| b.cmp(a))
+            .dedup()
+            .fold(N::new_constant(true), |c, (vi, b)| {
+                let f = N::new_constant(false);
+                if b {
+                    N::new_var(vi, f, c)
+                } else {
+                    N::new_var(vi, c, f)
+                }
+            })
+    }
+    /// `cube` is a conjunction of literals, which has a single path to the true terminal.
+    fn restrict_node(f: &N, cube: &N) -> N {
+        if f.is_constant().is_some() || cube.is_constant().is_some() {
+            return f.clone();
+        }
+        if f.is_complement() {
+            let n = BDD::restrict_node(&f.complement().unwrap(), cube);
+            return n.complement().unwrap();
+        }
+        let (fkey, ckey) = (f.unified_key(), cube.unified_key());
+        // the branch of the cube which isn't the false terminal
+        let next = |c: &N| {
+            let low = c.low().unwrap();
+            if low.is_constant() == Some(false) {
+                (true, c.high().unwrap())
+            } else {
+                (false, low)
+            }
+        };
+        if ckey < fkey {
+            return BDD::restrict_node(f, &next(cube).1);
+        }
+        let ff = N::new_constant(false);
+        let hash_key = (Operation::BddRestrict, f.clone(), cube.clone(), ff);
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let n = if fkey == ckey {
+            match next(cube) {
+                (true, c) => BDD::restrict_node(&f.high().unwrap(), &c),
+                (false, c) => BDD::restrict_node(&f.low().unwrap(), &c),
+            }
+        } else {
+            BDD::new_node(
+                fkey - 2,
+                BDD::restrict_node(&f.low().unwrap(), cube),
+                BDD::restrict_node(&f.high().unwrap(), cube),
+            )
+        };
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return `exists vars. self`, the disjunction of the cofactors for all
+    /// assignments to `vars`.
+    ///```
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::example;
+    ///
+    /// let major = BDD::new_from(example::majority());
+    /// let x2x3 = BDD::new_from(example::x2x3());
+    /// assert_eq!(major.exists(&[1]), major.restrict(1, true));
+    /// assert_eq!(major.forall(&[1]), x2x3);
+    ///```
+    pub fn exists(&self, vars: &[usize]) -> Self {
+        self.quantify(BinOp::Or, vars)
+    }
+    /// return `forall vars. self`, the conjunction of the cofactors for all
+    /// assignments to `vars`.
+    pub fn forall(&self, vars: &[usize]) -> Self {
+        self.quantify(BinOp::And, vars)
+    }
+    /// return the unique quantification of self over `vars`, the exclusive or
+    /// of the cofactors, which is the Boolean difference for a single var.
+    pub fn unique(&self, vars: &[usize]) -> Self {
+        self.quantify(BinOp::Xor, vars)
+    }
+    fn quantify(&self, op: BinOp, vars: &[usize]) -> Self {
+        let cube = BDD::cube_node(vars.iter().map(|vi| (*vi, true)));
+        BDD {
+            graph: BDD::quantify_node(op, &self.graph, &cube),
+            ..Default::default()
+        }
+    }
+    /// combine the cofactors by `op` for the vars in `cube`, a conjunction of positive literals.
+    fn quantify_node(op: BinOp, f: &N, cube: &N) -> N {
+        if cube.is_constant().is_some() {
+            return f.clone();
+        }
+        let (fkey, ckey) = (f.unified_key(), cube.unified_key());
+        if ckey < fkey || f.is_constant().is_some() {
+            // f doesn't depend on the var
+            return match op.diagonal() {
+                Residual::Identity => BDD::quantify_node(op, f, &cube.high().unwrap()),
+                Residual::Constant(b) => N::new_constant(b),
+                Residual::Negation => unreachable!(),
+            };
+        }
+        let hash_key = (
+            Operation::BddQuantify(op),
+            f.clone(),
+            cube.clone(),
+            N::default(),
+        );
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let n = if fkey == ckey {
+            let c = cube.high().unwrap();
+            let low = BDD::quantify_node(op, &f.low().unwrap(), &c);
+            match (op.left(low.is_constant() == Some(true)), low.is_constant()) {
+                // terminate early if `low` decides the result
+                (Residual::Constant(b), Some(_)) => N::new_constant(b),
+                _ => {
+                    let high = BDD::quantify_node(op, &f.high().unwrap(), &c);
+                    BDD::apply_node(op, &low, &high)
+                }
+            }
+        } else {
+            BDD::new_node(
+                fkey - 2,
+                BDD::quantify_node(op, &f.low().unwrap(), cube),
+                BDD::quantify_node(op, &f.high().unwrap(), cube),
+            )
+        };
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return `exists vars. self & other` without building the conjunction,
+    /// which is known as the relational product.
+    ///```
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::example;
+    ///
+    /// let major = BDD::new_from(example::majority());
+    /// let x1x3 = BDD::new_from(example::x1x3());
+    /// assert_eq!(major.and_exists(&x1x3, &[1, 3]), major.and(&x1x3).exists(&[1, 3]));
+    ///```
+    pub fn and_exists(&self, other: &Self, vars: &[usize]) -> Self {
+        let cube = BDD::cube_node(vars.iter().map(|vi| (*vi, true)));
+        BDD {
+            graph: BDD::and_exists_node(&self.graph, &other.graph, &cube),
+            ..Default::default()
+        }
+    }
+    fn and_exists_node(f: &N, g: &N, cube: &N) -> N {
+        let t = N::new_constant(true);
+        let ff = N::new_constant(false);
+        match (f.is_constant(), g.is_constant()) {
+            (Some(false), _) | (_, Some(false)) => return ff,
+            (Some(true), Some(true)) => return t,
+            (Some(true), None) => return BDD::quantify_node(BinOp::Or, g, cube),
+            (None, Some(true)) => return BDD::quantify_node(BinOp::Or, f, cube),
+            _ if f == g => return BDD::quantify_node(BinOp::Or, f, cube),
+            _ if f.complement().as_ref() == Some(g) => return ff,
+            _ => (),
+        }
+        if cube.is_constant().is_some() {
+            return BDD::ite_node(f, g, &ff);
+        }
+        let top = f.unified_key().min(g.unified_key());
+        if cube.unified_key() < top {
+            // neither depends on the var
+            return BDD::and_exists_node(f, g, &cube.high().unwrap());
+        }
+        let (f, g) = if operand_order(g) < operand_order(f) {
+            (g, f)
+        } else {
+            (f, g)
+        };
+        let hash_key = (Operation::BddAndExists, f.clone(), g.clone(), cube.clone());
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let cofactors = |n: &N| {
+            if n.unified_key() == top {
+                (n.low().unwrap(), n.high().unwrap())
+            } else {
+                (n.clone(), n.clone())
+            }
+        };
+        let (f0, f1) = cofactors(f);
+        let (g0, g1) = cofactors(g);
+        let n = if cube.unified_key() == top {
+            let c = cube.high().unwrap();
+            let low = BDD::and_exists_node(&f0, &g0, &c);
+            if low == t {
+                // terminate early, since the disjunction is already true
+                t
+            } else {
+                let high = BDD::and_exists_node(&f1, &g1, &c);
+                BDD::ite_node(&low, &t, &high)
+            }
+        } else {
+            BDD::new_node(
+                top - 2,
+                BDD::and_exists_node(&f0, &g0, cube),
+                BDD::and_exists_node(&f1, &g1, cube),
+            )
+        };
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return the diagram in which every var `vi` in `substitution` is replaced
+    /// with `substitution[vi]` simultaneously. Unlike a sequence of [`compose`],
+    /// a replacement isn't affected by the others even if it has their vars.
+    ///
+    /// [`compose`]: ReducedDecisionDiagram::compose
+    ///```
+    /// use std::collections::HashMap;
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::{example, Node};
+    /// use ddir::types::DecisionDiagramNode;
+    ///
+    /// let var = |vi| BDD::new_from(Node::new_var(vi, Node::new_constant(false), Node::new_constant(true)));
+    /// let x1x2x4 = BDD::new_from(example::x1x2x4());
+    /// // swap x1 and x2
+    /// let swapped = x1x2x4.vector_compose(&HashMap::from([(1, var(2)), (2, var(1))]));
+    /// assert_eq!(swapped.restrict(1, true).restrict(2, false), x1x2x4.restrict(1, false).restrict(2, true));
+    ///```
+    pub fn vector_compose(&self, substitution: &HashMap<usize, BDD<N>>) -> Self {
+        fn aux<N: DecisionDiagram<N> + DecisionDiagramNode>(
+            f: &N,
+            substitution: &HashMap<usize, BDD<N>>,
+            memo: &mut HashMap<N, N>,
+        ) -> N {
+            if f.is_constant().is_some() {
+                return f.clone();
+            }
+            if let Some(n) = memo.get(f) {
+                return n.clone();
+            }
+            let vi = f.var_index().unwrap();
+            let low = aux(&f.low().unwrap(), substitution, memo);
+            let high = aux(&f.high().unwrap(), substitution, memo);
+            let n = match substitution.get(&vi) {
+                Some(g) => BDD::ite_node(&g.graph, &high, &low),
+                None => BDD::ite_node(
+                    &N::new_var(vi, N::new_constant(false), N::new_constant(true)),
+                    &high,
+                    &low,
+                ),
+            };
+            memo.insert(f.clone(), n.clone());
+            n
+        }
+        BDD {
+            graph: aux(&self.graph, substitution, &mut HashMap::new()),
+            ..Default::default()
+        }
+    }
+    /// return the diagram in which every var `vi` in `map` is renamed to `map[vi]`.
+    /// It just relabels the nodes if the renaming keeps the order of the vars;
+    /// otherwise it rebuilds the diagram through `ite`.
+    ///```
+    /// use std::collections::HashMap;
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::example;
+    ///
+    /// let major = BDD::new_from(example::majority());
+    /// let next = major.rename(&HashMap::from([(1, 11), (2, 12), (3, 13)]));
+    /// assert_eq!(next.count_models(11..=13), 4);
+    /// assert_eq!(next.rename(&HashMap::from([(11, 1), (12, 2), (13, 3)])), major);
+    ///```
+    pub fn rename(&self, map: &HashMap<usize, usize>) -> Self {
+        let rename = |vi: usize| map.get(&vi).copied().unwrap_or(vi);
+        if preserves_order(&self.graph, rename) {
+            return BDD {
+                graph: relabel(&self.graph, &rename),
+                ..Default::default()
+            };
+        }
+        let substitution = map
+            .iter()
+            .map(|(from, to)| {
+                let f = N::new_constant(false);
+                let t = N::new_constant(true);
+                (*from, BDD::new_from(N::new_var(*to, f, t)))
+            })
+            .collect::<HashMap<_, _>>();
+        self.vector_compose(&substitution)
+    }
+    /// return the diagram in which every var `vi` is renamed to `permutation[vi]`.
+    /// The vars out of `permutation` are kept.
+    pub fn permute(&self, permutation: &[usize]) -> Self {
+        self.rename(&permutation.iter().copied().enumerate().collect())
+    }
+    /// return the negated diagram. It takes constant time and shares all nodes
+    /// with self if the node type supports complement edges.
+    pub fn negate(&self) -> Self {
+        BDD {
+            graph: BDD::negate_node(&self.graph),
+            ..Default::default()
+        }
+    }
+    fn negate_node(node: &N) -> N {
+        if let Some(n) = node.complement() {
+            return n;
+        }
+        if let Some(b) = node.is_constant() {
+            return N::new_constant(!b);
+        }
+        let f = N::new_constant(false);
+        let hash_key = (Operation::BddNegate, node.clone(), f.clone(), f);
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let n = N::new_var(
+            node.var_index().unwrap(),
+            BDD::negate_node(&node.low().unwrap()),
+            BDD::negate_node(&node.high().unwrap()),
+        );
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return the diagram of 'if self then `g` else `h`'.
+    /// All binary operators are evaluated through this, so they share the
+    /// entries in the computed table.
+    ///```
+    /// use ddir::bdd::BDD;
+    /// use ddir::node::{example, Node};
+    ///
+    /// let x1x3 = BDD::new_from(example::x1x3());
+    /// let x2x3 = BDD::new_from(example::x2x3());
+    /// assert_eq!(x1x3.ite(&x2x3, &x1x3.negate()), x1x3.implies(&x2x3));
+    ///```
+    pub fn ite(&self, g: &Self, h: &Self) -> Self {
+        BDD {
+            graph: BDD::ite_node(&self.graph, &g.graph, &h.graph),
+            ..Default::default()
+        }
+    }
+    pub fn and(&self, other: &Self) -> Self {
+        self.ite(other, &BDD::constant(false))
+    }
+    pub fn or(&self, other: &Self) -> Self {
+        self.ite(&BDD::constant(true), other)
+    }
+    pub fn xor(&self, other: &Self) -> Self {
+        self.ite(&other.negate(), other)
+    }
+    pub fn xnor(&self, other: &Self) -> Self {
+        self.ite(other, &other.negate())
+    }
+    pub fn nand(&self, other: &Self) -> Self {
+        self.ite(&other.negate(), &BDD::constant(true))
+    }
+    pub fn nor(&self, other: &Self) -> Self {
+        self.ite(&BDD::constant(false), &other.negate())
+    }
+    pub fn implies(&self, other: &Self) -> Self {
+        self.ite(other, &BDD::constant(true))
+    }
+    /// evaluate `op` through `ite`: `op(f, g) = ite(f, op(true, g), op(false, g))`.
+    fn apply_node(op: BinOp, f: &N, g: &N) -> N {
+        let cofactor = |a: bool| match op.left(a) {
+            Residual::Constant(b) => N::new_constant(b),
+            Residual::Identity => g.clone(),
+            Residual::Negation => BDD::negate_node(g),
+        };
+        BDD::ite_node(f, &cofactor(true), &cofactor(false))
+    }
+    fn constant(b: bool) -> Self {
+        BDD {
+            graph: N::new_constant(b),
+            ..Default::default()
+        }
+    }
+    fn ite_node(f: &N, g: &N, h: &N) -> N {
+        let t = N::new_constant(true);
+        let ff = N::new_constant(false);
+        // the terminal cases
+        let terminal = |f: &N, g: &N, h: &N| -> Option<N> {
+            match (f.is_constant(), g.is_constant(), h.is_constant()) {
+                (Some(true), _, _) => Some(g.clone()),
+                (Some(false), _, _) => Some(h.clone()),
+                _ if g == h => Some(g.clone()),
+                (_, Some(true), Some(false)) => Some(f.clone()),
+                (_, Some(false), Some(true)) => Some(BDD::negate_node(f)),
+                _ => None,
+            }
+        };
+        if let Some(n) = terminal(f, g, h) {
+            return n;
+        }
+        // replace the operands equivalent to `f` or `!f` with constants
+        let nf = f.complement();
+        let g = if g == f {
+            t.clone()
+        } else if nf.as_ref() == Some(g) {
+            ff.clone()
+        } else {
+            g.clone()
+        };
+        let h = if h == f {
+            ff.clone()
+        } else if nf.as_ref() == Some(h) {
+            t.clone()
+        } else {
+            h.clone()
+        };
+        if let Some(n) = terminal(f, &g, &h) {
+            return n;
+        }
+        // choose the standard triple among the equivalent ones, so that the
+        // operand which has the top var comes first. Ties are broken by hash.
+        let key = |n: &N| n.unified_key();
+        let order = operand_order::<N>;
+        let (mut f, mut g, mut h) = match (g.is_constant(), h.is_constant()) {
+            // f | h
+            (Some(true), None) if order(&h) < order(f) => (h, t.clone(), f.clone()),
+            // f & g
+            (None, Some(false)) if order(&g) < order(f) => (g, f.clone(), ff.clone()),
+            // !f | g
+            (None, Some(true)) if order(&g) < order(f) && nf.is_some() => {
+                (g.complement().unwrap(), nf.unwrap(), t.clone())
+            }
+            // !f & h
+            (Some(false), None) if order(&h) < order(f) && nf.is_some() => {
+                (h.complement().unwrap(), ff.clone(), nf.unwrap())
+            }
+            // f ^ !g
+            (None, None) if g.complement().as_ref() == Some(&h) && order(&g) < order(f) => {
+                (g, f.clone(), nf.unwrap())
+            }
+            _ => (f.clone(), g, h),
+        };
+        // make `f` and `g` regular edges
+        if f.is_complement() {
+            f = f.complement().unwrap();
+            (g, h) = (h, g);
+        }
+        let negated = g.is_complement();
+        if negated {
+            g = g.complement().unwrap();
+            h = h.complement().unwrap();
+        }
+        let hash_key = (Operation::BddIte, f.clone(), g.clone(), h.clone());
+        let node = if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            n
+        } else {
+            let top = [&f, &g, &h]
+                .iter()
+                .map(|n| key(n))
+                .filter(|k| 1 < *k)
+                .min()
+                .unwrap();
+            let cofactors = |n: &N| {
+                if key(n) == top {
+                    (n.low().unwrap(), n.high().unwrap())
+                } else {
+                    (n.clone(), n.clone())
+                }
+            };
+            let (f0, f1) = cofactors(&f);
+            let (g0, g1) = cofactors(&g);
+            let (h0, h1) = cofactors(&h);
+            let n = BDD::new_node(
+                top - 2,
+                BDD::ite_node(&f0, &g0, &h0),
+                BDD::ite_node(&f1, &g1, &h1),
+            );
+            N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+            n
+        };
+        if negated {
+            node.complement().unwrap()
+        } else {
+            node
+        }
+    }
 }
 
 impl<N: DecisionDiagram<N> + DecisionDiagramNode> DecisionDiagram<N> for BDD<N> {
-    fn all_nodes(&self) -> HashSet<&N> {
+    fn all_nodes(&self) -> HashSet<N> {
         self.graph.all_nodes()
     }
     fn len(&self) -> usize {
@@ -49,244 +643,119 @@ impl<N: DecisionDiagram<N> + DecisionDiagramNode> DecisionDiagram<N> for BDD<N>
     }
 }
 
-impl ReducedDecisionDiagram for BDD<Node> {
+impl<N: DecisionDiagram<N> + DecisionDiagramNode> ReducedDecisionDiagram for BDD<N> {
     // convert tree to BDD
     fn reduce(&mut self) {
-        let root = &self.graph;
-        let (mut index, mut node) = Node::build_indexer(&[root.clone()]);
-        let mut vlist: HashMap<usize, Vec<&Node>> = HashMap::new();
-        // put each vertex u on list vlist[u.var_index]
-        let mut bools = (false, false);
-        for n in root.all_nodes().iter().cloned() {
-            match n.unified_key() {
-                0 => bools.0 |= true,
-                1 => bools.1 |= true,
-                k => vlist.entry(k - 2).or_default().push(n),
-            }
-        }
-        match bools {
-            (false, false) => unreachable!(),
-            (true, false) => {
-                self.graph = node[&0].clone();
-                return;
-            }
-            (false, true) => {
-                self.graph = node[&1].clone();
-                return;
-            }
-            (true, true) => (),
-        }
-        let mut next_id: usize = index.len();
-        for vi in vlist.keys().sorted().rev() {
-            let mut q: Vec<((usize, usize), &Node)> = Vec::new();
-            for n in vlist[vi].iter().cloned() {
-                match **n {
-                    Vertex::Bool(_) => (),
-                    Vertex::Var {
-                        ref low, ref high, ..
-                    } => {
-                        if index[low] == index[high] {
-                            // redundant vertex
-                            index.insert(n.clone(), index[low]);
-                        } else {
-                            q.push(((index[low], index[high]), n));
-                        }
-                    }
-                }
-            }
-            q.sort_unstable_by_key(|(k, _)| *k);
-            let mut old_key: (usize, usize) = (usize::MAX, usize::MAX);
-            for (key, n) in q.iter().cloned() {
-                if key == old_key {
-                    index.insert(n.clone(), next_id);
-                } else {
-                    next_id += 1;
-                    match **n {
-                        Vertex::Bool(_) => {
-                            index.insert(n.clone(), next_id);
-                            node.insert(next_id, n.clone());
-                        }
-                        Vertex::Var {
-                            var_index,
-                            ref low,
-                            ref high,
-                        } => {
-                            let nn = Node::new_var(
-                                var_index,
-                                node[&index[low]].clone(),
-                                node[&index[high]].clone(),
-                            );
-                            index.insert(n.clone(), next_id);
-                            index.insert(nn.clone(), next_id);
-                            node.insert(next_id, nn);
-                        }
-                    }
-                    old_key = key;
-                }
-            }
+        self.graph = self.graph.rebuild(&BDD::new_node);
+    }
+    /// evaluate `op` through `ite`: `op(f, g) = ite(f, op(true, g), op(false, g))`.
+    fn apply(&self, op: BinOp, other: &Self) -> BDD<N> {
+        BDD {
+            graph: BDD::apply_node(op, &self.graph, &other.graph),
+            ..Default::default()
         }
-        // pick up a tree from the hash-table
-        self.graph = node[&next_id].clone();
-    }
-    fn apply(&self, op: Box<dyn Fn(bool, bool) -> bool>, unit: bool, other: &Self) -> BDD<Node> {
-        fn aux(
-            operator @ (op, unit): &BooleanOperator,
-            (v1, v2): (Node, Node),
-            indexer @ (index, node): &Indexer<Node>,
-            evaluation: &mut HashMap<Node, bool>,
-            merged: &mut HashMap<(usize, usize), Node>,
-        ) -> Node {
-            let hash_key = (index[&v1], index[&v2]);
-            if let Some(n) = merged.get(&hash_key) {
-                return n.clone(); // have already evaluaten
-            }
-            let value1 = evaluation.get(&v1);
-            let value2 = evaluation.get(&v2);
-            let value = match (value1, value2) {
-                (Some(a), _) if *a == *unit => Some(*unit),
-                (_, Some(b)) if *b == *unit => Some(*unit),
-                (None, _) | (_, None) => None,
-                (Some(a), Some(b)) => Some(op(*a, *b)),
-            };
-            if let Some(b) = value {
-                return node[&(b as usize)].clone();
-            }
-            let v1key = v1.unified_key();
-            let v2key = v2.unified_key();
-            let key = match (v1key < 2, v2key < 2) {
-                (false, false) => v1key.min(v2key),
-                (false, true) => v1key,
-                (true, false) => v2key,
-                (true, true) => op(v1key == 1, v2key == 1) as usize,
-            };
-            let u = if key < 2 {
-                Node::new_constant(key == 1)
-            } else {
-                let (vlow1, vhigh1) = if v1key == key {
-                    (v1.low().unwrap().clone(), v1.high().unwrap().clone())
-                } else {
-                    (v1.clone(), v1.clone())
-                };
-                let (vlow2, vhigh2) = if v2key == key {
-                    (v2.low().unwrap().clone(), v2.high().unwrap().clone())
-                } else {
-                    (v2.clone(), v2.clone())
-                };
-                Node::new_var(
-                    key - 2,
-                    aux(operator, (vlow1, vlow2), indexer, evaluation, merged),
-                    aux(operator, (vhigh1, vhigh2), indexer, evaluation, merged),
-                )
-            };
-            if let Some(b) = value {
-                evaluation.insert(u.clone(), b);
-            }
-            merged.insert(hash_key, u.clone());
-            u
-        }
-        // mapping from index pair to index
-        let mut merged: HashMap<(usize, usize), Node> = HashMap::new();
-        // mapping from node to bool
-        let mut evaluation: HashMap<Node, bool> = HashMap::new();
-        BDD::new_from(aux(
-            &(op, unit),
-            (self.graph.clone(), other.graph.clone()),
-            &Node::build_indexer(&[self.graph.clone(), other.graph.clone()]),
-            &mut evaluation,
-            &mut merged,
-        ))
     }
     /// return a new diagram by composing this and other
     fn compose(&self, other: &Self, var_index: usize) -> Self {
-        let v1 = self.graph.clone();
-        let v2 = other.graph.clone();
-        let mut indexer = Node::build_indexer(&[v1.clone(), v2.clone()]);
-        let mut links: HashMap<(usize, usize, usize), Node> = HashMap::new();
-        let mut values: HashMap<Node, bool> = HashMap::new();
-        values.insert(indexer.1[&0].clone(), false);
-        values.insert(indexer.1[&1].clone(), true);
-        BDD::new_from(compose_aux(
-            (&v1, &v1, &v2),
-            var_index,
-            &mut indexer.0,
-            &mut indexer.1,
-            &mut links,
-            &mut values,
-        ))
+        let v1 = &self.graph;
+        BDD {
+            graph: compose_aux((v1, v1, &other.graph), var_index),
+            ..Default::default()
+        }
     }
 }
 
-fn compose_aux(
-    (low, high, other): (&Node, &Node, &Node),
+fn compose_aux<N: DecisionDiagram<N> + DecisionDiagramNode>(
+    (low, high, other): (&N, &N, &N),
     control: usize,
-    index: &mut HashMap<Node, usize>,
-    node: &mut HashMap<usize, Node>,
-    links: &mut HashMap<(usize, usize, usize), Node>,
-    values: &mut HashMap<Node, bool>,
-) -> Node {
-    // let nodes = vec![low, high, other];
-    let hash_key = (index[low], index[high], index[other]);
+) -> N {
+    let hash_key = (
+        Operation::BddCompose(control),
+        low.clone(),
+        high.clone(),
+        other.clone(),
+    );
+    if let Some(evaluated) = N::with_computed_table(|t| t.get(&hash_key)) {
+        return evaluated;
+    }
     let vlow1 = if low.var_index() == Some(control) {
         low.low().unwrap()
     } else {
-        low
+        low.clone()
     };
     let vhigh1 = if high.var_index() == Some(control) {
         high.high().unwrap()
     } else {
-        high
+        high.clone()
     };
-    if let Some(evaluated) = links.get(&hash_key) {
-        return evaluated.clone();
-    }
     if let (Some(bl), Some(bh), Some(b2)) = (
-        values.get(&node[&index[vlow1]]),
-        values.get(&node[&index[vhigh1]]),
-        values.get(&node[&index[other]]),
+        vlow1.is_constant(),
+        vhigh1.is_constant(),
+        other.is_constant(),
     ) {
         // create terminal vertex
-        let val = ((!b2) & bl) | (b2 & bh);
-        links.insert(hash_key, node[&(val as usize)].clone());
-        node[&(val as usize)].clone()
+        let val = N::new_constant(((!b2) & bl) | (b2 & bh));
+        N::with_computed_table(|t| t.insert(hash_key, val.clone()));
+        val
     } else {
         // create nonterminal vertex and evaluate further down
-        let Some(vi) = [low.unified_key(), high.unified_key(), other.unified_key()]
-            .iter()
-            .filter(|n| 1 < **n)
-            .map(|k| *k - 2)
-            .min()
-        else {
+        let Some(vi) = [
+            vlow1.unified_key(),
+            vhigh1.unified_key(),
+            other.unified_key(),
+        ]
+        .iter()
+        .filter(|n| 1 < **n)
+        .map(|k| *k - 2)
+        .min() else {
             panic!();
         };
         let (vll1, vlh1) = if Some(vi) == vlow1.var_index() {
             (vlow1.low().unwrap(), vlow1.high().unwrap())
         } else {
-            (vlow1, vlow1)
+            (vlow1.clone(), vlow1.clone())
         };
         let (vhl1, vhh1) = if Some(vi) == vhigh1.var_index() {
             (vhigh1.low().unwrap(), vhigh1.high().unwrap())
         } else {
-            (vhigh1, vhigh1)
+            (vhigh1.clone(), vhigh1.clone())
         };
         let (vl2, vh2) = if Some(vi) == other.var_index() {
             (other.low().unwrap(), other.high().unwrap())
         } else {
-            (other, other)
+            (other.clone(), other.clone())
         };
-        let l = compose_aux((vll1, vhl1, vl2), control, index, node, links, values);
-        let h = compose_aux((vlh1, vhh1, vh2), control, index, node, links, values);
-        let u = Node::new_var(vi, l, h);
-        links.insert(hash_key, u.clone());
+        let l = compose_aux((&vll1, &vhl1, &vl2), control);
+        let h = compose_aux((&vlh1, &vhh1, &vh2), control);
+        let u = BDD::new_node(vi, l, h);
+        N::with_computed_table(|t| t.insert(hash_key, u.clone()));
         u
     }
 }
 
+/// return the BDD of the single var `vi`, shared by the tests
+#[cfg(test)]
+pub(crate) fn var<N: DecisionDiagram<N> + DecisionDiagramNode>(vi: usize) -> BDD<N> {
+    BDD::new_from(N::new_var(
+        vi,
+        N::new_constant(false),
+        N::new_constant(true),
+    ))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{
-        bdd::BDD,
-        node::{example, Node},
-        types::{DecisionDiagram, DecisionDiagramNode},
+    use {
+        crate::{
+            bdd::{BDD, var},
+            count::{BigUint, Count},
+            node::{Node, example},
+            types::{
+                Assignment, BinOp, DecisionDiagram, DecisionDiagramNode, ReducedDecisionDiagram,
+            },
+            zdd::ZDD,
+        },
+        std::collections::HashMap,
     };
 
     #[test]
@@ -299,11 +768,426 @@ mod test {
     #[test]
     fn test_satisfy_one() {
         let f = Node::new_constant(false);
-        assert_eq!(BDD::new_from(f.clone()).satisfy_one(), false);
+        assert!(!BDD::new_from(f.clone()).satisfy_one());
         let ff: Node = Node::new_var(2, f.clone(), f.clone());
         let bdd: BDD<Node> = BDD::new_from(ff);
-        assert_eq!(bdd.satisfy_one(), false);
+        assert!(!bdd.satisfy_one());
+        let major = BDD::new_from(example::majority());
+        assert!(major.satisfy_one());
+    }
+    #[test]
+    fn test_canonicity() {
+        let x1x3 = BDD::new_from(example::x1x3());
+        let x2x3 = BDD::new_from(example::x2x3());
+        assert_eq!(x1x3, BDD::new_from(example::x1x3()));
+        let or1 = x1x3.apply(BinOp::Or, &x2x3);
+        let or2 = x2x3.apply(BinOp::Or, &x1x3);
+        assert_eq!(or1, or2);
+        assert_eq!(or1.len(), 5);
+        let and = x1x3.apply(BinOp::And, &x2x3);
+        assert_ne!(or1, and);
+        assert_eq!(and, and.apply(BinOp::And, &and));
+    }
+    #[test]
+    fn test_compose() {
+        let x1x2x4 = BDD::new_from(example::x1x2x4());
+        let x2x3 = BDD::new_from(example::x2x3());
+        let composed = x1x2x4.compose(&x2x3, 2);
+        // x1 ? x2 x3 : (x2 x3 ? !x4 : true)
+        let expected = BDD::new_from(Node::new_var(
+            1,
+            Node::new_var(
+                2,
+                Node::new_constant(true),
+                Node::new_var(
+                    3,
+                    Node::new_constant(true),
+                    Node::new_var(4, Node::new_constant(true), Node::new_constant(false)),
+                ),
+            ),
+            example::x2x3(),
+        ));
+        assert_eq!(composed, expected);
+    }
+    #[test]
+    fn test_satisfy_one_model() {
+        let f = BDD::new_from(Node::new_constant(false));
+        assert_eq!(f.satisfy_one_model(), None);
+        assert_eq!(f.negate().satisfy_one_model(), Some(Assignment::new()));
+        let diagrams = [
+            BDD::new_from(example::x1x3()),
+            BDD::new_from(example::x1x2x4()),
+            BDD::new_from(example::majority()),
+            BDD::new_from(example::majority()).negate(),
+            BDD::new_from(example::independent_set()),
+            BDD::new_from(example::kernels()).negate(),
+        ];
+        for d in diagrams.iter() {
+            let model = d.satisfy_one_model().unwrap();
+            // every completion of the model satisfies the diagram
+            for bits in 0..128 {
+                let bits = model
+                    .iter()
+                    .fold(bits, |b, (vi, v)| (b & !(1 << vi)) | ((*v as usize) << vi));
+                assert!(eval(&d.graph, bits));
+            }
+        }
+    }
+    #[test]
+    fn test_cubes() {
+        let f = BDD::new_from(Node::new_constant(false));
+        assert_eq!(f.cubes().count(), 0);
+        assert_eq!(
+            f.negate().cubes().collect::<Vec<_>>(),
+            vec![Assignment::new()]
+        );
+        assert_eq!(f.negate().assignments(1..=3).count(), 8);
+        for d in [
+            BDD::new_from(example::x1x3()),
+            BDD::new_from(example::x1x2x4()),
+            BDD::new_from(example::majority()).negate(),
+            BDD::new_from(example::independent_set()),
+            BDD::new_from(example::kernels()),
+        ] {
+            let cubes = d.cubes().collect::<Vec<_>>();
+            assert_eq!(cubes.len(), d.path_count());
+            assert_eq!(
+                cubes.iter().map(|c| 1 << (6 - c.len())).sum::<usize>(),
+                d.count_models(1..=6)
+            );
+            let mut models = d.assignments(1..=6).collect::<Vec<_>>();
+            assert_eq!(models.len(), d.count_models(1..=6));
+            assert!(models.iter().all(|m| m.len() == 6));
+            let bits = |m: &Assignment| m.iter().map(|(vi, b)| (*b as usize) << vi).sum();
+            assert!(models.iter().all(|m| eval(&d.graph, bits(m))));
+            models.sort_by_key(bits);
+            models.dedup_by_key(|m| bits(m));
+            assert_eq!(models.len(), d.count_models(1..=6));
+        }
+        // stop early on a huge solution space
+        let x2x3 = BDD::new_from(example::x2x3());
+        assert_eq!(x2x3.assignments(0..100).take(5).count(), 5);
+    }
+    #[test]
+    fn test_count_models() {
+        let f = BDD::new_from(Node::new_constant(false));
+        assert_eq!(f.count_models(1..=3), 0);
+        assert_eq!(f.negate().count_models(1..=3), 8);
+        // a gap over 1024 vars overflows f64 without making the sum NaN
+        assert_eq!(f.count_models_as::<f64>(0..1100), 0.0);
+        let x2x3: BDD<Node> = BDD::new_from(example::x2x3());
+        assert_eq!(x2x3.count_models_as::<f64>(0..1100), f64::INFINITY);
+        assert_eq!(
+            x2x3.count_models_as::<BigUint>(0..1100),
+            BigUint::one().shl(1098)
+        );
+        assert_eq!(f.negate().count_models([]), 1);
+        let x1x3 = BDD::new_from(example::x1x3());
+        assert_eq!(x1x3.path_count(), 2);
+        assert_eq!(x1x3.count_models(1..=3), 6);
+        assert_eq!(x1x3.count_models([3, 1]), 3);
+        let x2x3 = BDD::new_from(example::x2x3());
+        assert_eq!(x2x3.count_models(0..=5), 16);
+        assert_eq!(x1x3.or(&x2x3).count_models(1..=3), 7);
+        let ind = BDD::new_from(example::independent_set());
+        assert!(ind.path_count() < 18);
+        assert_eq!(ind.count_models(1..=6), 18);
+        assert_eq!(ind.negate().count_models(1..=6), 64 - 18);
+        let krn = BDD::new_from(example::kernels());
+        assert_eq!(
+            krn.count_models(1..=6),
+            ZDD::new_from(example::kernels()).satisfy_all()
+        );
+    }
+    #[test]
+    #[should_panic]
+    fn test_count_models_out_of_universe() {
+        BDD::new_from(example::x1x3()).count_models(1..=2);
+    }
+    #[test]
+    fn test_restrict() {
+        let t = BDD::new_from(Node::new_constant(true));
         let major = BDD::new_from(example::majority());
-        assert_eq!(major.satisfy_one(), true);
+        let x1x3 = BDD::new_from(example::x1x3());
+        let x2x3 = BDD::new_from(example::x2x3());
+        assert_eq!(major.restrict(1, false), x2x3);
+        // x2 | x3
+        let x2_x3 = BDD::new_from(Node::new_var(
+            2,
+            Node::new_var(3, Node::new_constant(false), Node::new_constant(true)),
+            Node::new_constant(true),
+        ));
+        assert_eq!(major.restrict(1, true), x2_x3);
+        assert_eq!(major.restrict(4, true), major);
+        assert_eq!(major.negate().restrict(1, false), x2x3.negate());
+        assert_eq!(x1x3.restrict(1, false), t);
+        assert_eq!(
+            major.restrict_cube(&Assignment::from([(1, true), (3, false)])),
+            major.restrict(3, false).restrict(1, true)
+        );
+        assert_eq!(major.restrict_cube(&Assignment::new()), major);
+        // Shannon expansion and the truth table
+        for d in [
+            major.clone(),
+            BDD::new_from(example::x1x2x4()),
+            BDD::new_from(example::independent_set()),
+            BDD::new_from(example::kernels()).negate(),
+        ] {
+            for vi in 1..=6 {
+                let x: BDD<Node> = var(vi);
+                assert_eq!(x.ite(&d.restrict(vi, true), &d.restrict(vi, false)), d);
+            }
+            let cube = Assignment::from([(2, true), (4, false), (5, true)]);
+            let r = d.restrict_cube(&cube);
+            for bits in 0..128 {
+                assert_eq!(
+                    eval(&r.graph, bits),
+                    eval(&d.graph, bits & !0b10000 | 0b100100)
+                );
+            }
+        }
+    }
+    #[test]
+    fn test_quantify() {
+        let f = BDD::new_from(Node::new_constant(false));
+        let t = f.negate();
+        let major = BDD::new_from(example::majority());
+        let x1x3 = BDD::new_from(example::x1x3());
+        assert_eq!(major.exists(&[]), major);
+        assert_eq!(major.exists(&[1, 2, 3]), t);
+        assert_eq!(major.forall(&[1, 2, 3]), f);
+        assert_eq!(major.exists(&[5]), major);
+        assert_eq!(major.unique(&[5]), f);
+        assert_eq!(
+            major.exists(&[1]),
+            major.restrict(1, false).or(&major.restrict(1, true))
+        );
+        assert_eq!(major.forall(&[2]).negate(), major.negate().exists(&[2]));
+        assert_eq!(
+            major.unique(&[1]),
+            major.restrict(1, false).xor(&major.restrict(1, true))
+        );
+        assert_eq!(x1x3.forall(&[3]), x1x3.restrict(3, true));
+        // a repeated var is quantified once
+        assert_eq!(major.unique(&[1, 1]), major.unique(&[1]));
+        assert_eq!(major.exists(&[3, 1, 3]), major.exists(&[1, 3]));
+        assert_eq!(major.forall(&[2, 2]), major.forall(&[2]));
+        for d in [
+            major.clone(),
+            BDD::new_from(example::x1x2x4()),
+            BDD::new_from(example::independent_set()),
+            BDD::new_from(example::kernels()).negate(),
+        ] {
+            let vars = [2, 4, 5];
+            let exists = vars.iter().fold(d.clone(), |acc, vi| {
+                acc.restrict(*vi, false).or(&acc.restrict(*vi, true))
+            });
+            let forall = vars.iter().fold(d.clone(), |acc, vi| {
+                acc.restrict(*vi, false).and(&acc.restrict(*vi, true))
+            });
+            let unique = vars.iter().fold(d.clone(), |acc, vi| {
+                acc.restrict(*vi, false).xor(&acc.restrict(*vi, true))
+            });
+            assert_eq!(d.exists(&vars), exists);
+            assert_eq!(d.forall(&vars), forall);
+            assert_eq!(d.unique(&vars), unique);
+        }
+    }
+    #[test]
+    fn test_and_exists() {
+        let f = BDD::new_from(Node::new_constant(false));
+        let t = f.negate();
+        let major = BDD::new_from(example::majority());
+        assert_eq!(major.and_exists(&major.negate(), &[1]), f);
+        assert_eq!(major.and_exists(&t, &[1]), major.exists(&[1]));
+        assert_eq!(major.and_exists(&major, &[]), major);
+        assert_eq!(major.and_exists(&major, &[1, 2, 3]), t);
+        let diagrams = [
+            major.clone(),
+            BDD::new_from(example::x1x3()),
+            BDD::new_from(example::x1x2x4()),
+            BDD::new_from(example::independent_set()),
+            BDD::new_from(example::kernels()).negate(),
+        ];
+        for a in diagrams.iter() {
+            for b in diagrams.iter() {
+                for vars in [&[1][..], &[2, 4], &[1, 3, 5, 6], &[7]] {
+                    assert_eq!(a.and_exists(b, vars), a.and(b).exists(vars));
+                    assert_eq!(a.and_exists(b, vars), b.and_exists(a, vars));
+                }
+            }
+        }
+    }
+    #[test]
+    fn test_vector_compose() {
+        let major = BDD::new_from(example::majority());
+        let x1x3 = BDD::new_from(example::x1x3());
+        let x2x3 = BDD::new_from(example::x2x3());
+        let x1x2x4 = BDD::new_from(example::x1x2x4());
+        // a single substitution is compose
+        assert_eq!(
+            x1x2x4.vector_compose(&HashMap::from([(2, x2x3.clone())])),
+            x1x2x4.compose(&x2x3, 2)
+        );
+        // swapping vars can't be done by a sequence of compose
+        let swap = HashMap::from([(1, var(3)), (3, var(1))]);
+        let sequential = x1x2x4.compose(&var(3), 1).compose(&var(1), 3);
+        assert_eq!(x1x3.vector_compose(&swap), x1x3);
+        assert_eq!(major.vector_compose(&swap), major);
+        assert_ne!(x1x2x4.vector_compose(&swap), sequential);
+        let substitution =
+            HashMap::from([(1, x2x3.clone()), (2, major.clone()), (4, x1x3.negate())]);
+        for d in [
+            major.clone(),
+            x1x2x4.clone(),
+            BDD::new_from(example::independent_set()),
+            BDD::new_from(example::kernels()).negate(),
+        ] {
+            let r = d.vector_compose(&substitution);
+            for bits in 0..128usize {
+                let value = |g: &BDD<Node>| eval(&g.graph, bits) as usize;
+                let substituted = bits & !0b10110
+                    | value(&x2x3) << 1
+                    | value(&major) << 2
+                    | value(&x1x3.negate()) << 4;
+                assert_eq!(eval(&r.graph, bits), eval(&d.graph, substituted));
+            }
+        }
+    }
+    #[test]
+    fn test_rename() {
+        let major = BDD::new_from(example::majority());
+        let x1x2x4 = BDD::new_from(example::x1x2x4());
+        let ind = BDD::new_from(example::independent_set());
+        // order-preserving renamings keep the shape
+        let shifted = ind.permute(&[0, 11, 12, 13, 14, 15, 16]);
+        assert_eq!(shifted.len(), ind.len());
+        assert_eq!(shifted.count_models(11..=16), 18);
+        assert_eq!(shifted.permute(&[0; 11]), shifted);
+        assert_eq!(
+            major
+                .rename(&HashMap::from([(3, 7)]))
+                .count_models([1, 2, 7]),
+            4
+        );
+        // renamings changing the order
+        let swap = HashMap::from([(1, 4), (4, 1)]);
+        let swapped = x1x2x4.rename(&swap);
+        assert_eq!(
+            swapped,
+            x1x2x4.vector_compose(&HashMap::from([(1, var(4)), (4, var(1))]))
+        );
+        assert_eq!(swapped.rename(&swap), x1x2x4);
+        assert_eq!(swapped.count_models(1..=4), x1x2x4.count_models(1..=4));
+        let reversed = ind.permute(&[0, 6, 5, 4, 3, 2, 1]);
+        // the independent sets of a cycle are symmetric
+        assert_eq!(reversed, ind);
+        let rotated = ind.permute(&[0, 2, 3, 4, 5, 6, 1]);
+        assert_eq!(rotated, ind);
+        let moved = major.permute(&[0, 5, 2, 1]);
+        for bits in 0..64usize {
+            let original = (bits >> 5 & 1) << 1 | bits & 0b100 | (bits >> 1 & 1) << 3;
+            assert_eq!(eval(&moved.graph, bits), eval(&major.graph, original));
+        }
+    }
+    #[test]
+    fn test_negate() {
+        let major = BDD::new_from(example::majority());
+        let minor = major.negate();
+        assert_ne!(major, minor);
+        assert_eq!(minor.negate(), major);
+        assert_eq!(minor.len(), major.len());
+        assert_eq!(minor.satisfy_all(), 3);
+        let f = BDD::new_from(Node::new_constant(false));
+        let and = major.apply(BinOp::And, &minor);
+        let or = major.apply(BinOp::Or, &minor);
+        assert_eq!(and, f);
+        assert_eq!(or, f.negate());
+        // the negation built from a tree is the same as the complement edge
+        let x1x3 = BDD::new_from(example::x1x3());
+        let nx1x3 = BDD::new_from(Node::new_var(
+            1,
+            Node::new_constant(false),
+            example::x2x3().high().unwrap(),
+        ));
+        assert_eq!(nx1x3, x1x3.negate());
+    }
+    #[test]
+    fn test_ite() {
+        let f = BDD::new_from(Node::new_constant(false));
+        let t = f.negate();
+        let x1x3 = BDD::new_from(example::x1x3());
+        let x2x3 = BDD::new_from(example::x2x3());
+        let major = BDD::new_from(example::majority());
+        assert_eq!(t.ite(&x1x3, &x2x3), x1x3);
+        assert_eq!(f.ite(&x1x3, &x2x3), x2x3);
+        assert_eq!(major.ite(&t, &f), major);
+        assert_eq!(major.ite(&f, &t), major.negate());
+        assert_eq!(major.ite(&major, &x1x3), major.or(&x1x3));
+        assert_eq!(x1x3.and(&x2x3), x2x3.and(&x1x3));
+        assert_eq!(x1x3.or(&x2x3), x2x3.or(&x1x3));
+        assert_eq!(x1x3.xor(&x2x3), x2x3.xor(&x1x3));
+        assert_eq!(x1x3.xor(&x1x3), f);
+        assert_eq!(x1x3.xnor(&x2x3), x1x3.xor(&x2x3).negate());
+        assert_eq!(x1x3.nand(&x2x3), x1x3.negate().or(&x2x3.negate()));
+        assert_eq!(x1x3.nor(&x2x3), x1x3.negate().and(&x2x3.negate()));
+        assert_eq!(x1x3.implies(&x2x3), x1x3.negate().or(&x2x3));
+        assert_eq!(major.xor(&x1x3).xor(&x1x3), major);
+        // apply evaluates its operator through ite
+        assert_eq!(x1x3.apply(BinOp::Xor, &t), x1x3.negate());
+        assert_eq!(x1x3.apply(BinOp::And, &x2x3), x1x3.and(&x2x3));
+        assert_eq!(x1x3.apply(BinOp::Implies, &x2x3), x1x3.implies(&x2x3));
+        assert_eq!(
+            x1x3.apply(BinOp::ConverseDiff, &x2x3),
+            x1x3.nor(&x2x3.negate())
+        );
+        assert_eq!(x1x3.apply(BinOp::NotB, &x2x3), x2x3.negate());
+    }
+    #[test]
+    fn test_ite_shares_cache() {
+        let x1x3 = BDD::new_from(example::x1x3());
+        let major = BDD::new_from(example::majority());
+        let and = x1x3.and(&major);
+        let before = Node::with_computed_table(|t| t.stats());
+        let applied = major.apply(BinOp::And, &x1x3);
+        let after = Node::with_computed_table(|t| t.stats());
+        assert_eq!(and, applied);
+        assert_eq!(after.misses, before.misses);
+    }
+    /// evaluate `node` under the assignment in which var `i` is `bits >> i & 1`
+    fn eval(node: &Node, bits: usize) -> bool {
+        match node.is_constant() {
+            Some(b) => b,
+            None if bits >> node.var_index().unwrap() & 1 == 1 => eval(&node.high().unwrap(), bits),
+            None => eval(&node.low().unwrap(), bits),
+        }
+    }
+    #[test]
+    fn test_ite_truth_table() {
+        let f = BDD::new_from(Node::new_constant(false));
+        let diagrams = [
+            f.clone(),
+            f.negate(),
+            BDD::new_from(example::x1x3()),
+            BDD::new_from(example::x2x3()),
+            BDD::new_from(example::x1x2x4()),
+            BDD::new_from(example::majority()),
+            BDD::new_from(example::majority()).negate(),
+        ];
+        for a in diagrams.iter() {
+            for b in diagrams.iter() {
+                for c in diagrams.iter() {
+                    let r = a.ite(b, c);
+                    for bits in 0..32 {
+                        let expected = if eval(&a.graph, bits) {
+                            eval(&b.graph, bits)
+                        } else {
+                            eval(&c.graph, bits)
+                        };
+                        assert_eq!(eval(&r.graph, bits), expected);
+                    }
+                }
+            }
+        }
     }
 }
@@ -0,0 +1,210 @@
+//! Computed table to memoize diagram operations across calls
+use {
+    crate::types::{BinOp, DecisionDiagramNode},
+    std::hash::{BuildHasher, BuildHasherDefault, DefaultHasher, Hash, RandomState},
+};
+
+/// The operations memoized in a computed table.
+/// Each variant holds the parameters which are not nodes.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Operation {
+    /// BDD composition at a var
+    BddCompose(usize),
+    /// BDD negation for node types without complement edges
+    BddNegate,
+    /// BDD relational product with a cube given as the third operand
+    BddAndExists,
+    /// BDD if-then-else
+    BddIte,
+    /// BDD quantification combining the cofactors by an operator
+    BddQuantify(BinOp),
+    /// BDD restriction by a cube given as the second operand
+    BddRestrict,
+    /// ZDD apply
+    ZddApply(BinOp),
+    /// ZDD toggling an element in every set
+    ZddChange(usize),
+    /// ZDD product of the sets which are disjoint
+    ZddDisjointProduct,
+    /// ZDD weak division
+    ZddDivide,
+    /// ZDD minimal hitting sets
+    ZddHittingSets,
+    /// ZDD addition of an element to every set
+    ZddInsert(usize),
+    /// ZDD product by the union of sets
+    ZddJoin,
+    /// ZDD maximal sets
+    ZddMaximal,
+    /// ZDD product by the intersection of sets
+    ZddMeet,
+    /// ZDD minimal sets
+    ZddMinimal,
+    /// ZDD sets which are contained in no set of the other
+    ZddNonSub,
+    /// ZDD sets which contain no set of the other
+    ZddNonSup,
+    /// ZDD sets whose sizes are in a range
+    ZddSize(usize, usize),
+    /// ZDD sets which contain an element or not
+    ZddSubset(usize, bool),
+}
+
+/// return a total order on nodes, used to choose a standard key among the
+/// equivalent ones. The node which has the top var comes first, and ties are
+/// broken by a hash which is stable in a run.
+pub(crate) fn operand_order<N: DecisionDiagramNode>(n: &N) -> (usize, u64) {
+    (
+        n.unified_key(),
+        BuildHasherDefault::<DefaultHasher>::default().hash_one(n),
+    )
+}
+
+/// the key of an entry: an operation and up to three operand nodes
+pub type CacheKey<N> = (Operation, N, N, N);
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    pub capacity: usize,
+    /// the number of occupied slots
+    pub entries: usize,
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl CacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        match self.hits + self.misses {
+            0 => 0.0,
+            n => self.hits as f64 / n as f64,
+        }
+    }
+}
+
+/// A direct-mapped and size-bounded cache from operations to their results.
+/// A new entry overwrites the old one in the same slot, so that the memory
+/// usage never exceeds the capacity.
+/// Note: a table with a too small capacity makes operations slow.
+#[derive(Debug)]
+pub struct ComputedTable<N> {
+    slots: Vec<Option<(CacheKey<N>, N)>>,
+    capacity: usize,
+    hasher: RandomState,
+    stats: CacheStats,
+}
+
+impl<N> Default for ComputedTable<N> {
+    fn default() -> Self {
+        ComputedTable::new(ComputedTable::<N>::DEFAULT_CAPACITY)
+    }
+}
+
+impl<N> ComputedTable<N> {
+    pub const DEFAULT_CAPACITY: usize = 1 << 16;
+    /// return an empty table which has `capacity`, rounded up to a power of two, slots.
+    pub fn new(capacity: usize) -> Self {
+        ComputedTable {
+            slots: Vec::new(),
+            capacity: capacity.max(1).next_power_of_two(),
+            hasher: RandomState::new(),
+            stats: CacheStats::default(),
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    /// change the capacity. All entries are discarded.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1).next_power_of_two();
+        self.slots = Vec::new();
+        self.stats.entries = 0;
+    }
+    /// discard all entries. The statistics are kept.
+    pub fn clear(&mut self) {
+        self.slots = Vec::new();
+        self.stats.entries = 0;
+    }
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            capacity: self.capacity,
+            ..self.stats
+        }
+    }
+    pub fn reset_stats(&mut self) {
+        self.stats = CacheStats {
+            entries: self.stats.entries,
+            ..CacheStats::default()
+        };
+    }
+}
+
+impl<N: Clone + Eq + Hash> ComputedTable<N> {
+    fn slot(&self, key: &CacheKey<N>) -> usize {
+        self.hasher.hash_one(key) as usize & (self.capacity - 1)
+    }
+    /// return the memoized result of `key`
+    pub fn get(&mut self, key: &CacheKey<N>) -> Option<N> {
+        let i = self.slot(key);
+        match self.slots.get(i) {
+            Some(Some((k, value))) if k == key => {
+                self.stats.hits += 1;
+                Some(value.clone())
+            }
+            _ => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+    /// memoize the result of `key`
+    pub fn insert(&mut self, key: CacheKey<N>, value: N) {
+        if self.slots.is_empty() {
+            self.slots.resize_with(self.capacity, || None);
+        }
+        let i = self.slot(&key);
+        if self.slots[i].replace((key, value)).is_none() {
+            self.stats.entries += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            bdd::BDD,
+            node::{Node, example},
+            types::ReducedDecisionDiagram,
+        },
+    };
+
+    #[test]
+    fn test_computed_table() {
+        let mut table: ComputedTable<Node> = ComputedTable::new(3);
+        assert_eq!(table.capacity(), 4);
+        let f = Node::new_constant(false);
+        let t = Node::new_constant(true);
+        let key = (Operation::BddIte, t.clone(), f.clone(), f.clone());
+        assert_eq!(table.get(&key), None);
+        table.insert(key.clone(), t.clone());
+        assert_eq!(table.get(&key), Some(t.clone()));
+        assert_eq!(table.stats().hits, 1);
+        assert_eq!(table.stats().misses, 1);
+        assert_eq!(table.stats().entries, 1);
+        table.clear();
+        assert_eq!(table.get(&key), None);
+    }
+    #[test]
+    fn test_shared_between_calls() {
+        let x1x3 = BDD::new_from(example::x1x3());
+        let x2x3 = BDD::new_from(example::x2x3());
+        let or1 = x1x3.apply(BinOp::Or, &x2x3);
+        let before = Node::with_computed_table(|t| t.stats());
+        let or2 = x2x3.apply(BinOp::Or, &x1x3);
+        let after = Node::with_computed_table(|t| t.stats());
+        assert_eq!(or1, or2);
+        assert_eq!(after.hits, before.hits + 1);
+        assert_eq!(after.misses, before.misses);
+    }
+}
@@ -0,0 +1,276 @@
+//! Numeric types to count models and sets
+use {
+    crate::types::DecisionDiagramNode,
+    std::{cmp::Ordering, collections::HashMap, fmt},
+};
+
+/// A numeric type which counts the models of a BDD or the sets in a ZDD.
+/// `usize`, `u64` and `u128` panic on overflow; `f64` loses precision instead;
+/// and [`BigUint`] is always exact.
+pub trait Count: Clone {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn add(&self, other: &Self) -> Self;
+    /// return `self * 2^k`
+    fn shl(&self, k: usize) -> Self;
+}
+
+macro_rules! impl_count {
+    ($t: ty) => {
+        impl Count for $t {
+            fn zero() -> Self {
+                0
+            }
+            fn one() -> Self {
+                1
+            }
+            fn add(&self, other: &Self) -> Self {
+                self.checked_add(*other).expect("count overflow")
+            }
+            fn shl(&self, k: usize) -> Self {
+                if *self == 0 {
+                    return 0;
+                }
+                u32::try_from(k)
+                    .ok()
+                    .and_then(|k| (2 as $t).checked_pow(k))
+                    .and_then(|p| self.checked_mul(p))
+                    .expect("count overflow")
+            }
+        }
+    };
+}
+
+impl_count!(usize);
+impl_count!(u64);
+impl_count!(u128);
+
+impl Count for f64 {
+    fn zero() -> Self {
+        0.0
+    }
+    fn one() -> Self {
+        1.0
+    }
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+    fn shl(&self, k: usize) -> Self {
+        // avoid `0 * inf`, which is NaN
+        if *self == 0.0 {
+            return 0.0;
+        }
+        self * 2f64.powi(k.min(i32::MAX as usize) as i32)
+    }
+}
+
+/// An arbitrary-precision unsigned integer, which supports the operations
+/// needed to count.
+///```
+/// use ddir::count::{BigUint, Count};
+///
+/// let n = BigUint::one().shl(100).add(&BigUint::from(7u64));
+/// assert_eq!(n.to_string(), "1267650600228229401496703205383");
+/// assert_eq!(n.to_u128(), Some((1 << 100) + 7));
+///```
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct BigUint {
+    /// the limbs from the least significant one, without trailing zeros
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    fn normalize(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+    /// return the value if it fits in `u128`.
+    pub fn to_u128(&self) -> Option<u128> {
+        match self.limbs[..] {
+            [] => Some(0),
+            [a] => Some(a as u128),
+            [a, b] => Some((b as u128) << 64 | a as u128),
+            _ => None,
+        }
+    }
+    /// return the nearest `f64`, which may be infinite.
+    pub fn to_f64(&self) -> f64 {
+        self.limbs
+            .iter()
+            .rev()
+            .fold(0.0, |acc, l| acc * 2f64.powi(64) + *l as f64)
+    }
+    /// divide self by `d` in place and return the remainder.
+    fn div_rem_small(&mut self, d: u64) -> u64 {
+        let mut rem: u128 = 0;
+        for l in self.limbs.iter_mut().rev() {
+            let x = rem << 64 | *l as u128;
+            *l = (x / d as u128) as u64;
+            rem = x % d as u128;
+        }
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        rem as u64
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        BigUint { limbs: vec![n] }.normalize()
+    }
+}
+
+impl From<u128> for BigUint {
+    fn from(n: u128) -> Self {
+        BigUint {
+            limbs: vec![n as u64, (n >> 64) as u64],
+        }
+        .normalize()
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 10^19 is the largest power of ten in u64
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut n = self.clone();
+        let mut chunks: Vec<u64> = Vec::new();
+        while !n.limbs.is_empty() {
+            chunks.push(n.div_rem_small(CHUNK));
+        }
+        let Some(top) = chunks.pop() else {
+            return f.pad("0");
+        };
+        let mut s = top.to_string();
+        for c in chunks.iter().rev() {
+            s.push_str(&format!("{c:019}"));
+        }
+        f.pad(&s)
+    }
+}
+
+impl Count for BigUint {
+    fn zero() -> Self {
+        BigUint::default()
+    }
+    fn one() -> Self {
+        BigUint::from(1u64)
+    }
+    fn add(&self, other: &Self) -> Self {
+        let len = self.limbs.len().max(other.limbs.len());
+        let mut limbs: Vec<u64> = Vec::with_capacity(len + 1);
+        let mut carry = false;
+        for i in 0..len {
+            let a = self.limbs.get(i).copied().unwrap_or(0);
+            let b = other.limbs.get(i).copied().unwrap_or(0);
+            let (s, c1) = a.overflowing_add(b);
+            let (s, c2) = s.overflowing_add(carry as u64);
+            limbs.push(s);
+            carry = c1 || c2;
+        }
+        if carry {
+            limbs.push(1);
+        }
+        BigUint { limbs }
+    }
+    fn shl(&self, k: usize) -> Self {
+        if self.limbs.is_empty() {
+            return self.clone();
+        }
+        let (words, bits) = (k / 64, k % 64);
+        let mut limbs: Vec<u64> = vec![0; words];
+        let mut carry: u64 = 0;
+        for l in self.limbs.iter() {
+            limbs.push(l << bits | carry);
+            carry = if bits == 0 { 0 } else { l >> (64 - bits) };
+        }
+        limbs.push(carry);
+        BigUint { limbs }.normalize()
+    }
+}
+
+/// return the weighted number of paths from `root` to the true terminal.
+/// An edge from `parent` to `child` is weighted by `2^gap(parent, child)`.
+pub(crate) fn count_paths<N, C>(root: &N, gap: &impl Fn(&N, &N) -> usize) -> C
+where
+    N: DecisionDiagramNode,
+    C: Count,
+{
+    fn count<N: DecisionDiagramNode, C: Count>(
+        node: &N,
+        gap: &impl Fn(&N, &N) -> usize,
+        memo: &mut HashMap<N, C>,
+    ) -> C {
+        if let Some(b) = node.is_constant() {
+            return if b { C::one() } else { C::zero() };
+        }
+        if let Some(c) = memo.get(node) {
+            return c.clone();
+        }
+        let low = node.low().unwrap();
+        let high = node.high().unwrap();
+        let c = count(&low, gap, memo)
+            .shl(gap(node, &low))
+            .add(&count(&high, gap, memo).shl(gap(node, &high)));
+        memo.insert(node.clone(), c.clone());
+        c
+    }
+    count(root, gap, &mut HashMap::new())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_big_uint() {
+        let zero = BigUint::zero();
+        assert_eq!(zero.to_string(), "0");
+        assert_eq!(zero.shl(100), zero);
+        let n = BigUint::from(u64::MAX).add(&BigUint::one());
+        assert_eq!(n, BigUint::one().shl(64));
+        assert_eq!(n.to_u128(), Some(1 << 64));
+        let m = BigUint::from(3u64).shl(64).add(&BigUint::from(5u64));
+        assert_eq!(m.to_string(), "55340232221128654853");
+        assert!(n < m);
+        assert_eq!(
+            BigUint::one().shl(200).to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert_eq!(BigUint::one().shl(200).to_f64(), 2f64.powi(200));
+        assert_eq!(BigUint::one().shl(200).to_u128(), None);
+        assert_eq!(format!("{:>4}", BigUint::from(12u64)), "  12");
+    }
+    #[test]
+    fn test_fast_paths() {
+        assert_eq!(3u128.shl(100), 3 << 100);
+        assert_eq!(0usize.shl(1000), 0);
+        assert_eq!(1.5f64.shl(3), 12.0);
+        assert_eq!(0f64.shl(2000), 0.0);
+        assert_eq!(1f64.shl(2000), f64::INFINITY);
+        assert_eq!(Count::add(&u64::MAX, &0), u64::MAX);
+    }
+    #[test]
+    #[should_panic]
+    fn test_overflow() {
+        1u64.shl(64);
+    }
+}
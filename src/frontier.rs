@@ -0,0 +1,414 @@
+//! Frontier-based search to build the ZDDs of subgraphs
+//!
+//! The edges of a graph are decided in order. The frontier is the set of
+//! vertices which are incident to both a decided edge and an undecided one,
+//! and a search state holds the degrees and connected components of them.
+//! The search is a [`DdSpec`], so the equal states at a level are merged and
+//! the ZDD is built from them without the decision tree.
+use crate::{
+    spec::{Child, DdSpec},
+    types::{DecisionDiagram, DecisionDiagramNode},
+    zdd::ZDD,
+};
+
+/// An undirected graph on the vertices `0..vertices`. Its edges are ordered,
+/// and the `i`-th edge is represented by var `i` in ZDDs.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Graph {
+    vertices: usize,
+    edges: Vec<(usize, usize)>,
+}
+
+/// The kinds of subgraphs to enumerate. A subgraph is the set of its edges.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Subgraph {
+    /// simple paths between two distinct vertices
+    Path(usize, usize),
+    /// simple cycles
+    Cycle,
+    /// spanning trees
+    SpanningTree,
+    /// acyclic subgraphs, including the empty one
+    Forest,
+    /// matchings, including the empty one
+    Matching,
+}
+
+/// the degree and the component of each vertex in the frontier, indexed by
+/// the position in it. A component is labeled by the position of its first member.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Mate {
+    degree: Vec<u8>,
+    component: Vec<usize>,
+}
+
+/// the specification of the subgraphs of a kind in a graph
+struct Frontier<'a> {
+    graph: &'a Graph,
+    kind: Subgraph,
+    /// the level at which each vertex leaves the frontier
+    last: Vec<Option<usize>>,
+    /// the vertices incident to the edge at each level or in the frontier
+    /// before it, in ascending order
+    active: Vec<Vec<usize>>,
+    /// the positions in `active` of the frontier before each level
+    carried: Vec<Vec<usize>>,
+    /// the result when it's decided without search
+    trivial: Option<bool>,
+}
+
+impl Graph {
+    /// return a graph. The order of `edges` is the var order in ZDDs.
+    ///```
+    /// use ddir::frontier::{Graph, Subgraph};
+    /// use ddir::node::Node;
+    /// use ddir::zdd::ZDD;
+    ///
+    /// // the complete graph on 4 vertices
+    /// let k4 = Graph::new(4, [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+    /// let trees: ZDD<Node> = k4.enumerate(Subgraph::SpanningTree);
+    /// assert_eq!(trees.count_sets(), 16);
+    /// let cycles: ZDD<Node> = k4.enumerate(Subgraph::Cycle);
+    /// assert_eq!(cycles.count_sets(), 7);
+    /// assert!(cycles.sets().any(|c| c == vec![0, 1, 3]));
+    ///```
+    pub fn new(vertices: usize, edges: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let edges = edges.into_iter().collect::<Vec<_>>();
+        assert!(
+            edges.iter().all(|(u, v)| *u < vertices && *v < vertices),
+            "a vertex out of the graph"
+        );
+        assert!(edges.iter().all(|(u, v)| u != v), "a self-loop");
+        Graph { vertices, edges }
+    }
+    pub fn vertices(&self) -> usize {
+        self.vertices
+    }
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+    /// return the family of the edge sets of the subgraphs of `kind`.
+    pub fn enumerate<N>(&self, kind: Subgraph) -> ZDD<N>
+    where
+        N: DecisionDiagram<N> + DecisionDiagramNode,
+    {
+        ZDD::from_spec(&Frontier::new(self, kind))
+    }
+}
+
+impl<'a> Frontier<'a> {
+    fn new(graph: &'a Graph, kind: Subgraph) -> Self {
+        let n = graph.vertices;
+        let m = graph.edges.len();
+        // the levels at which each vertex enters and leaves the frontier
+        let mut first: Vec<Option<usize>> = vec![None; n];
+        let mut last: Vec<Option<usize>> = vec![None; n];
+        for (i, (u, v)) in graph.edges.iter().enumerate() {
+            for w in [*u, *v] {
+                first[w].get_or_insert(i);
+                last[w] = Some(i);
+            }
+        }
+        let isolated = first.iter().any(|f| f.is_none());
+        let trivial = match kind {
+            Subgraph::Path(s, t) => {
+                assert!(s < n && t < n, "a vertex out of the graph");
+                assert_ne!(s, t, "a path between the same vertex");
+                (first[s].is_none() || first[t].is_none()).then_some(false)
+            }
+            Subgraph::SpanningTree if isolated => Some(n == 1),
+            _ if m == 0 => Some(matches!(kind, Subgraph::Forest | Subgraph::Matching)),
+            _ => None,
+        };
+        let mut active: Vec<Vec<usize>> = Vec::with_capacity(m);
+        let mut carried: Vec<Vec<usize>> = Vec::with_capacity(m);
+        let mut frontier: Vec<usize> = Vec::new();
+        for (i, (u, v)) in graph.edges.iter().enumerate() {
+            let mut vertices = frontier.clone();
+            for w in [*u, *v] {
+                if first[w] == Some(i) && !vertices.contains(&w) {
+                    vertices.push(w);
+                }
+            }
+            vertices.sort_unstable();
+            carried.push(
+                frontier
+                    .iter()
+                    .map(|w| vertices.binary_search(w).unwrap())
+                    .collect(),
+            );
+            frontier = vertices
+                .iter()
+                .copied()
+                .filter(|w| last[*w] != Some(i))
+                .collect();
+            active.push(vertices);
+        }
+        Frontier {
+            graph,
+            kind,
+            last,
+            active,
+            carried,
+            trivial,
+        }
+    }
+    /// decide the `i`-th edge from `mate`, the state on the frontier before it.
+    fn step(&self, i: usize, mate: &Mate, take: bool) -> Child<Mate> {
+        let kind = self.kind;
+        let active = &self.active[i];
+        let (u, v) = self.graph.edges[i];
+        let (pu, pv) = (
+            active.binary_search(&u).unwrap(),
+            active.binary_search(&v).unwrap(),
+        );
+        // expand the state to the active vertices, in which the entering ones are isolated
+        let mut degree = vec![0; active.len()];
+        let mut component = (0..active.len()).collect::<Vec<_>>();
+        let carried = &self.carried[i];
+        for (q, p) in carried.iter().enumerate() {
+            degree[*p] = mate.degree[q];
+            component[*p] = carried[mate.component[q]];
+        }
+        let capacity = |w: usize| match kind {
+            Subgraph::Path(s, t) if w == s || w == t => 1,
+            Subgraph::Matching => 1,
+            _ => 2,
+        };
+        if take {
+            if matches!(
+                kind,
+                Subgraph::Path(..) | Subgraph::Cycle | Subgraph::Matching
+            ) && (degree[pu] == capacity(u) || degree[pv] == capacity(v))
+            {
+                return Child::Terminal(false);
+            }
+            let (cu, cv) = (component[pu], component[pv]);
+            if cu == cv {
+                // the edge closes a cycle
+                if kind != Subgraph::Cycle {
+                    return Child::Terminal(false);
+                }
+                // the cycle must be the only component with edges
+                let open = (0..active.len()).any(|p| p != pu && p != pv && degree[p] == 1);
+                return Child::Terminal(!open);
+            }
+            degree[pu] += 1;
+            degree[pv] += 1;
+            let (keep, drop) = (cu.min(cv), cu.max(cv));
+            for c in component.iter_mut() {
+                if *c == drop {
+                    *c = keep;
+                }
+            }
+        }
+        let leaves = |p: usize| self.last[active[p]] == Some(i);
+        for p in (0..active.len()).filter(|p| leaves(*p)) {
+            let w = active[p];
+            match kind {
+                Subgraph::Path(s, t) if (w == s || w == t) && degree[p] != 1 => {
+                    return Child::Terminal(false);
+                }
+                Subgraph::Path(s, t) if w == s || w == t => (),
+                Subgraph::Path(..) | Subgraph::Cycle if degree[p] == 1 => {
+                    return Child::Terminal(false);
+                }
+                Subgraph::SpanningTree => {
+                    // the vertices which stay after the leaving ones so far
+                    let mut remaining = (0..active.len()).filter(|x| p < *x || !leaves(*x));
+                    if remaining.clone().all(|x| component[x] != component[p]) {
+                        // the component is closed, so it must be the whole graph
+                        return Child::Terminal(
+                            remaining.next().is_none() && i + 1 == self.graph.edges.len(),
+                        );
+                    }
+                }
+                _ => (),
+            }
+        }
+        if i + 1 == self.graph.edges.len() {
+            // every vertex has left the frontier
+            return Child::Terminal(!matches!(kind, Subgraph::Cycle | Subgraph::SpanningTree));
+        }
+        // shrink the state to the frontier, and relabel each component by its first member
+        let mut label: Vec<Option<usize>> = vec![None; active.len()];
+        let mut next = Mate {
+            degree: Vec::new(),
+            component: Vec::new(),
+        };
+        for p in (0..active.len()).filter(|p| !leaves(*p)) {
+            let position = next.degree.len();
+            next.degree.push(degree[p]);
+            next.component
+                .push(*label[component[p]].get_or_insert(position));
+        }
+        Child::Node(i + 1, next)
+    }
+}
+
+impl DdSpec for Frontier<'_> {
+    type State = Mate;
+    fn root(&self) -> Child<Mate> {
+        match self.trivial {
+            Some(b) => Child::Terminal(b),
+            None => Child::Node(
+                0,
+                Mate {
+                    degree: Vec::new(),
+                    component: Vec::new(),
+                },
+            ),
+        }
+    }
+    fn child(&self, mate: &Mate, var_index: usize, branch: bool) -> Child<Mate> {
+        self.step(var_index, mate, branch)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{arena::ArenaNode, node::Node},
+        itertools::Itertools,
+    };
+
+    /// return the grid graph of `rows` x `cols` vertices
+    fn grid(rows: usize, cols: usize) -> Graph {
+        let mut edges = Vec::new();
+        for r in 0..rows {
+            for c in 0..cols {
+                let v = r * cols + c;
+                if c + 1 < cols {
+                    edges.push((v, v + 1));
+                }
+                if r + 1 < rows {
+                    edges.push((v, v + cols));
+                }
+            }
+        }
+        Graph::new(rows * cols, edges)
+    }
+    /// check whether the edge set `set` is a subgraph of `kind` by definition
+    fn is_a(graph: &Graph, kind: Subgraph, set: &[usize]) -> bool {
+        let n = graph.vertices();
+        let mut degree = vec![0; n];
+        let mut root = (0..n).collect::<Vec<_>>();
+        fn find(root: &mut [usize], v: usize) -> usize {
+            if root[v] != v {
+                root[v] = find(root, root[v]);
+            }
+            root[v]
+        }
+        let mut acyclic = true;
+        for i in set.iter() {
+            let (u, v) = graph.edges()[*i];
+            degree[u] += 1;
+            degree[v] += 1;
+            let (ru, rv) = (find(&mut root, u), find(&mut root, v));
+            acyclic &= ru != rv;
+            root[ru] = rv;
+        }
+        let touched = (0..n).filter(|v| 0 < degree[*v]).collect::<Vec<_>>();
+        let components = touched.iter().map(|v| find(&mut root, *v)).unique().count();
+        match kind {
+            Subgraph::Path(s, t) => {
+                acyclic
+                    && components == 1
+                    && degree[s] == 1
+                    && degree[t] == 1
+                    && touched.iter().all(|v| degree[*v] <= 2)
+            }
+            Subgraph::Cycle => components == 1 && touched.iter().all(|v| degree[*v] == 2),
+            Subgraph::SpanningTree => acyclic && set.len() + 1 == n,
+            Subgraph::Forest => acyclic,
+            Subgraph::Matching => degree.iter().all(|d| *d <= 1),
+        }
+    }
+    #[test]
+    fn test_by_definition() {
+        let graphs = [
+            grid(2, 3),
+            grid(3, 3),
+            Graph::new(4, [(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]),
+            // parallel edges and an isolated vertex
+            Graph::new(5, [(0, 1), (1, 2), (0, 1), (2, 3), (1, 3), (0, 3)]),
+            Graph::new(3, [(2, 0), (1, 2)]),
+        ];
+        for graph in graphs.iter() {
+            let kinds = [
+                Subgraph::Path(0, graph.vertices() - 2),
+                Subgraph::Path(1, 0),
+                Subgraph::Cycle,
+                Subgraph::SpanningTree,
+                Subgraph::Forest,
+                Subgraph::Matching,
+            ];
+            let m = graph.edges().len();
+            for kind in kinds {
+                let expected = (0..1usize << m)
+                    .map(|bits| (0..m).filter(|i| bits >> i & 1 == 1).collect::<Vec<_>>())
+                    .filter(|set| is_a(graph, kind, set))
+                    .sorted()
+                    .collect::<Vec<_>>();
+                let zdd: ZDD<Node> = graph.enumerate(kind);
+                assert_eq!(
+                    zdd.sets().sorted().collect::<Vec<_>>(),
+                    expected,
+                    "{kind:?} of {graph:?}"
+                );
+                let arena: ZDD<ArenaNode> = graph.enumerate(kind);
+                assert_eq!(arena.count_sets(), expected.len());
+            }
+        }
+    }
+    #[test]
+    fn test_large() {
+        let g = grid(5, 5);
+        let paths: ZDD<Node> = g.enumerate(Subgraph::Path(0, 24));
+        assert_eq!(paths.count_sets(), 8512);
+        let trees: ZDD<Node> = g.enumerate(Subgraph::SpanningTree);
+        assert_eq!(trees.count_sets(), 557568000);
+        let cycles: ZDD<Node> = grid(4, 4).enumerate(Subgraph::Cycle);
+        assert_eq!(cycles.count_sets(), 213);
+    }
+    #[test]
+    fn test_frontier_width() {
+        // the states are as wide as the frontier, not the whole ladder
+        let ladder = grid(60, 2);
+        let spec = Frontier::new(&ladder, Subgraph::SpanningTree);
+        assert!(spec.active.iter().all(|a| a.len() <= 3));
+        assert!(spec.carried.iter().all(|c| c.len() <= 2));
+        let trees: ZDD<Node> = ladder.enumerate(Subgraph::SpanningTree);
+        // the spanning trees of a ladder follow t(n) = 4 t(n - 1) - t(n - 2)
+        let expected = (2..60).fold((1u128, 4u128), |(a, b), _| (b, 4 * b - a)).1;
+        assert_eq!(trees.count_sets_as::<u128>(), expected);
+    }
+    #[test]
+    fn test_trivial() {
+        let single = Graph::new(1, []);
+        assert_eq!(
+            single.enumerate::<Node>(Subgraph::SpanningTree),
+            ZDD::base()
+        );
+        assert_eq!(single.enumerate::<Node>(Subgraph::Cycle), ZDD::empty());
+        let disconnected = Graph::new(3, [(0, 1)]);
+        assert_eq!(
+            disconnected.enumerate::<Node>(Subgraph::SpanningTree),
+            ZDD::empty()
+        );
+        assert_eq!(
+            disconnected.enumerate::<Node>(Subgraph::Path(0, 2)),
+            ZDD::empty()
+        );
+        let halves = Graph::new(4, [(0, 1), (2, 3)]);
+        assert_eq!(
+            halves.enumerate::<Node>(Subgraph::SpanningTree),
+            ZDD::empty()
+        );
+        assert_eq!(
+            disconnected.enumerate::<Node>(Subgraph::Matching),
+            ZDD::from_sets([vec![], vec![0]])
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Lazy iterators over the solutions of Decision Diagrams
+use crate::types::{Assignment, DecisionDiagramNode};
+
+/// a var index and its value
+type Decision = (usize, bool);
+
+/// An iterator over the paths to the true terminal, in depth-first order
+/// trying `low` first. Each path is a sequence of decisions.
+#[derive(Clone, Debug)]
+struct Paths<N> {
+    /// the nodes to visit, with the path length at the visit and the decision to them
+    stack: Vec<(N, usize, Option<Decision>)>,
+    path: Vec<Decision>,
+}
+
+impl<N: DecisionDiagramNode> Paths<N> {
+    fn new(root: &N) -> Self {
+        Paths {
+            stack: vec![(root.clone(), 0, None)],
+            path: Vec::new(),
+        }
+    }
+}
+
+impl<N: DecisionDiagramNode> Iterator for Paths<N> {
+    type Item = Vec<Decision>;
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth, decision)) = self.stack.pop() {
+            self.path.truncate(depth);
+            self.path.extend(decision);
+            match node.is_constant() {
+                Some(true) => return Some(self.path.clone()),
+                Some(false) => (),
+                None => {
+                    let vi = node.var_index().unwrap();
+                    let depth = self.path.len();
+                    self.stack
+                        .push((node.high().unwrap(), depth, Some((vi, true))));
+                    self.stack
+                        .push((node.low().unwrap(), depth, Some((vi, false))));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over the cubes of a BDD, which are the partial assignments
+/// on the paths to the true terminal.
+#[derive(Clone, Debug)]
+pub struct Cubes<N>(Paths<N>);
+
+impl<N: DecisionDiagramNode> Cubes<N> {
+    pub(crate) fn new(root: &N) -> Self {
+        Cubes(Paths::new(root))
+    }
+}
+
+impl<N: DecisionDiagramNode> Iterator for Cubes<N> {
+    type Item = Assignment;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|path| path.into_iter().collect())
+    }
+}
+
+/// An iterator over the member sets of a ZDD. Each set is in ascending order.
+#[derive(Clone, Debug)]
+pub struct Sets<N>(Paths<N>);
+
+impl<N: DecisionDiagramNode> Sets<N> {
+    pub(crate) fn new(root: &N) -> Self {
+        Sets(Paths::new(root))
+    }
+}
+
+impl<N: DecisionDiagramNode> Iterator for Sets<N> {
+    type Item = Vec<usize>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|path| {
+            path.into_iter()
+                .filter(|(_, b)| *b)
+                .map(|(vi, _)| vi)
+                .collect()
+        })
+    }
+}
+
+/// An iterator over the full assignments to a universe, which expands each
+/// cube over the vars missing in it.
+#[derive(Clone, Debug)]
+pub struct Assignments<N> {
+    cubes: Cubes<N>,
+    universe: Vec<usize>,
+    /// the current cube, its don't-care vars and their values
+    current: Option<(Assignment, Vec<usize>, Vec<bool>)>,
+}
+
+impl<N: DecisionDiagramNode> Assignments<N> {
+    /// All vars in the diagram must be in `universe`.
+    pub(crate) fn new(root: &N, universe: Vec<usize>) -> Self {
+        Assignments {
+            cubes: Cubes::new(root),
+            universe,
+            current: None,
+        }
+    }
+}
+
+impl<N: DecisionDiagramNode> Iterator for Assignments<N> {
+    type Item = Assignment;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current.is_none() {
+            let cube = self.cubes.next()?;
+            assert!(
+                cube.keys().all(|vi| self.universe.contains(vi)),
+                "a var out of the universe"
+            );
+            let free = self
+                .universe
+                .iter()
+                .filter(|vi| !cube.contains_key(vi))
+                .copied()
+                .collect::<Vec<_>>();
+            let values = vec![false; free.len()];
+            self.current = Some((cube, free, values));
+        }
+        let (cube, free, values) = self.current.as_mut().unwrap();
+        let mut assignment = cube.clone();
+        assignment.extend(free.iter().copied().zip(values.iter().copied()));
+        // count up the values of the don't-care vars
+        match values.iter().position(|b| !*b) {
+            Some(i) => {
+                values[..i].fill(false);
+                values[i] = true;
+            }
+            None => self.current = None,
+        }
+        Some(assignment)
+    }
+}
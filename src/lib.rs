@@ -1,4 +1,11 @@
+pub mod arena;
 pub mod bdd;
+pub mod cache;
+pub mod count;
+pub mod frontier;
+pub mod iter;
 pub mod node;
+pub mod ops;
+pub mod spec;
 pub mod types;
 pub mod zdd;
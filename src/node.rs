@@ -1,21 +1,120 @@
 //! Element type for Decision Diagrams
 use {
-    crate::types::{DecisionDiagram, DecisionDiagramNode, Indexer},
+    crate::{
+        cache::ComputedTable,
+        types::{DecisionDiagram, DecisionDiagramNode},
+    },
+    itertools::Itertools,
     std::{
+        cell::RefCell,
         collections::{HashMap, HashSet},
         hash::Hash,
         io, ptr,
-        rc::Rc,
+        rc::{Rc, Weak},
     },
 };
 
-pub type Node = Rc<Vertex>;
+/// An edge to a shared vertex. A complement edge stands for the negation of
+/// the function of its vertex. Since only high edges can be complemented in
+/// vertices, every function has a unique representation, and the negation is
+/// a constant-time operation.
+/// `low` and `high` propagate the complement attribute to the children, so that
+/// traversing a diagram through them doesn't have to care about it.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Node {
+    vertex: Rc<Vertex>,
+    complement: bool,
+}
+
+thread_local! {
+    /// the shared terminal vertex
+    static TERMINAL: Rc<Vertex> = Rc::new(Vertex::Terminal);
+    /// the unique table to hash-cons non-terminal nodes
+    static UNIQUE_TABLE: RefCell<UniqueTable> = RefCell::new(UniqueTable::default());
+    /// the computed table shared by operations on nodes
+    static COMPUTED_TABLE: RefCell<ComputedTable<Node>> = RefCell::new(ComputedTable::default());
+}
+
+/// A mapping from `(var_index, low, high)` to the vertex which has them.
+/// Vertices are held weakly, so that unreachable ones are released.
+#[derive(Debug, Default)]
+struct UniqueTable {
+    table: HashMap<(usize, usize, usize), Weak<Vertex>>,
+    /// the table size at which stale entries are swept next time
+    threshold: usize,
+}
+
+impl UniqueTable {
+    const MIN_THRESHOLD: usize = 1024;
+    fn get_or_insert(&mut self, var_index: usize, low: Node, high: Node) -> Rc<Vertex> {
+        let key = (var_index, identity(&low), identity(&high));
+        if let Some(vertex) = self.table.get(&key).and_then(Weak::upgrade) {
+            return vertex;
+        }
+        let vertex = Rc::new(Vertex::Var {
+            var_index,
+            low,
+            high,
+        });
+        self.table.insert(key, Rc::downgrade(&vertex));
+        if self.threshold.max(Self::MIN_THRESHOLD) < self.table.len() {
+            self.sweep();
+        }
+        vertex
+    }
+    fn sweep(&mut self) {
+        self.table.retain(|_, n| 0 < n.strong_count());
+        self.threshold = 2 * self.table.len();
+    }
+}
+
+/// return the key to identify an edge in the unique table.
+/// Since a live vertex holds its children, their addresses are never reused.
+/// And the lowest bit of an address is free for the complement attribute.
+fn identity(node: &Node) -> usize {
+    Rc::as_ptr(&node.vertex) as usize | node.complement as usize
+}
+
+/// return the number of live non-terminal vertices in the unique table of this thread.
+///```
+/// use ddir::node::{self, Node};
+/// use ddir::types::DecisionDiagramNode;
+///
+/// let f = Node::new_constant(false);
+/// let t = Node::new_constant(true);
+/// let size = node::unique_table_size();
+/// let n = Node::new_var(1, f.clone(), t.clone());
+/// assert_eq!(node::unique_table_size(), size + 1);
+/// assert_eq!(n, Node::new_var(1, f, t));
+/// assert_eq!(node::unique_table_size(), size + 1);
+/// drop(n);
+/// node::sweep_unique_table();
+/// assert_eq!(node::unique_table_size(), size);
+///```
+pub fn unique_table_size() -> usize {
+    UNIQUE_TABLE.with(|t| {
+        t.borrow()
+            .table
+            .values()
+            .filter(|n| 0 < n.strong_count())
+            .count()
+    })
+}
+
+/// release the entries of the released vertices in the unique table of this thread.
+/// They are also swept as the table grows.
+pub fn sweep_unique_table() {
+    UNIQUE_TABLE.with(|t| t.borrow_mut().sweep());
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum Vertex {
-    Bool(bool),
+    /// the terminal for true; false is the complement edge to it
+    #[default]
+    Terminal,
     Var {
         var_index: usize,
+        /// a regular edge
         low: Node,
         high: Node,
     },
@@ -35,14 +134,47 @@ impl Hash for Vertex {
     }
 }
 
-impl Default for Vertex {
+impl Default for Node {
     fn default() -> Self {
-        Vertex::Bool(false)
+        Node::new_constant(false)
+    }
+}
+
+impl Node {
+    /// return the edge to the same vertex with the complement attribute flipped.
+    fn flip(&self) -> Node {
+        Node {
+            vertex: self.vertex.clone(),
+            complement: !self.complement,
+        }
+    }
+    /// return the regular edges to all non-terminal vertices under self.
+    fn vertices(&self) -> Vec<Node> {
+        let mut visited: HashSet<Rc<Vertex>> = HashSet::new();
+        let mut stack: Vec<Rc<Vertex>> = vec![self.vertex.clone()];
+        let mut vertices: Vec<Node> = Vec::new();
+        while let Some(v) = stack.pop() {
+            if let Vertex::Var {
+                ref low, ref high, ..
+            } = *v
+                && visited.insert(v.clone())
+            {
+                stack.push(low.vertex.clone());
+                stack.push(high.vertex.clone());
+                vertices.push(Node {
+                    vertex: v.clone(),
+                    complement: false,
+                });
+            }
+        }
+        vertices
     }
 }
 
 impl DecisionDiagram<Node> for Node {
-    /// returns the number of nodes under self and self itself.
+    /// returns the number of nodes under self and self itself, as read through
+    /// `low` and `high`. So a node and its complement count as two, though they
+    /// share a vertex, and it agrees with node types without complement edges.
     ///```
     /// use ddir::node::Node;
     /// use ddir::types::{DecisionDiagram, DecisionDiagramNode};
@@ -53,11 +185,14 @@ impl DecisionDiagram<Node> for Node {
     /// assert_eq!(n.len(), 2);
     /// let k = Node::new_var(1, n.clone(), f.clone());
     /// assert_eq!(k.len(), 3);
+    /// // `n` and its complement
+    /// let c = Node::new_var(1, n.clone(), n.complement().unwrap());
+    /// assert_eq!(c.len(), 5);
     ///```
     fn len(&self) -> usize {
         self.all_nodes().len()
     }
-    /// returns all nodes under self and self itself.
+    /// returns all nodes under self and self itself, as read through `low` and `high`.
     ///```
     /// use ddir::node::Node;
     /// use ddir::types::{DecisionDiagram, DecisionDiagramNode};
@@ -67,21 +202,23 @@ impl DecisionDiagram<Node> for Node {
     /// let k = Node::new_var(1, n.clone(), f.clone());
     /// assert_eq!(k.all_nodes().len(), 3);
     ///```
-    fn all_nodes<'a>(&'a self) -> HashSet<&'a Node> {
-        let mut map: HashSet<&'a Node> = HashSet::new();
-        fn traverse<'a>(node: &'a Node, map: &mut HashSet<&'a Node>) {
-            map.insert(node);
-            if let Vertex::Var {
-                ref low, ref high, ..
-            } = **node
-            {
-                traverse(low, map);
-                traverse(high, map);
+    fn all_nodes(&self) -> HashSet<Node> {
+        let mut map: HashSet<Node> = HashSet::new();
+        fn traverse(node: &Node, map: &mut HashSet<Node>) {
+            if !map.insert(node.clone()) {
+                return;
+            }
+            if let (Some(low), Some(high)) = (node.low(), node.high()) {
+                traverse(&low, map);
+                traverse(&high, map);
             }
         }
         traverse(self, &mut map);
         map
     }
+    /// writes the vertices and marks complement edges with a hollow arrowhead.
+    /// Edges to the terminal are drawn to the boxes of their values.
+    /// The marks show the internal encoding as a BDD; ZDDs are written without them.
     fn write_as_gv(&self, mut sink: impl io::Write) -> io::Result<()> {
         sink.write_all(
             b"digraph regexp {{
@@ -89,17 +226,37 @@ impl DecisionDiagram<Node> for Node {
   node [fontname=\"Helvetica,Arial,sans-serif\"]
   edge [fontname=\"Helvetica,Arial,sans-serif\",color=blue]\n",
         )?;
-        let mut index: HashMap<&Node, usize> = HashMap::new();
-        let mut bools = (false, false);
-        for (i, n) in self.all_nodes().iter().enumerate() {
-            index.insert(*n, i + 2);
-            match n.is_constant() {
-                Some(false) => bools.0 |= true,
-                Some(true) => bools.1 |= true,
-                None => (),
+        let vertices = self.vertices();
+        let index: HashMap<&Rc<Vertex>, usize> = vertices
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (&n.vertex, i + 2))
+            .collect();
+        let id = |n: &Node| {
+            n.is_constant()
+                .map_or_else(|| index[&n.vertex], |b| b as usize)
+        };
+        let arrow = |n: &Node| {
+            if n.complement && n.is_constant().is_none() {
+                ",arrowhead=odot"
+            } else {
+                ""
             }
-        }
+        };
         // nodes
+        let mut bools = (
+            self.is_constant() == Some(false),
+            self.is_constant() == Some(true),
+        );
+        for n in vertices.iter() {
+            for e in [n.low().unwrap(), n.high().unwrap()] {
+                match e.is_constant() {
+                    Some(false) => bools.0 |= true,
+                    Some(true) => bools.1 |= true,
+                    None => (),
+                }
+            }
+        }
         if bools.0 {
             sink.write_all(
                 b"  0[style=filled,fillcolor=\"gray80\",label=\"false\",shape=\"box\"];\n",
@@ -110,90 +267,213 @@ impl DecisionDiagram<Node> for Node {
                 b"  1[style=filled,fillcolor=\"gray95\",label=\"true\",shape=\"box\"];\n",
             )?;
         }
-        for node in self.all_nodes().iter() {
-            if let Vertex::Var { ref var_index, .. } = ***node {
-                let i = if let Some(b) = node.is_constant() {
-                    b as usize
-                } else {
-                    *index.get(node).unwrap()
-                };
-                sink.write_all(format!("  {i}[label=\"{var_index}\"];\n").as_bytes())?;
-            }
+        for n in vertices.iter() {
+            let i = id(n);
+            let var_index = n.var_index().unwrap();
+            sink.write_all(format!("  {i}[label=\"{var_index}\"];\n").as_bytes())?;
+        }
+        if self.complement && self.is_constant().is_none() {
+            let i = id(self);
+            sink.write_all(
+                format!("  root[shape=point];\n  root -> {i}[arrowhead=odot];\n").as_bytes(),
+            )?;
         }
         // edges
-        for node in self.all_nodes().iter() {
-            if let Vertex::Var {
-                ref low, ref high, ..
-            } = ***node
-            {
-                let i = if let Some(b) = node.is_constant() {
-                    b as usize
-                } else {
-                    *index.get(node).unwrap()
-                };
-                let j = if let Some(b) = low.is_constant() {
-                    b as usize
-                } else {
-                    *index.get(&low).unwrap()
-                };
-                let k = if let Some(b) = high.is_constant() {
-                    b as usize
-                } else {
-                    *index.get(&high).unwrap()
-                };
-                if j == k {
-                    sink.write_all(format!("  {i} -> {j}[color=black,penwidth=2];\n").as_bytes())?;
-                } else {
-                    sink.write_all(
-                        format!("  {i} -> {j}[color=red,style=\"dotted\"];\n").as_bytes(),
-                    )?;
-                    sink.write_all(format!("  {i} -> {k}[color=blue];\n").as_bytes())?;
-                }
+        for n in vertices.iter() {
+            let i = id(n);
+            let low = n.low().unwrap();
+            let high = n.high().unwrap();
+            let j = id(&low);
+            let k = id(&high);
+            if low == high {
+                sink.write_all(format!("  {i} -> {j}[color=black,penwidth=2];\n").as_bytes())?;
+            } else {
+                sink.write_all(format!("  {i} -> {j}[color=red,style=\"dotted\"];\n").as_bytes())?;
+                let a = arrow(&high);
+                sink.write_all(format!("  {i} -> {k}[color=blue{a}];\n").as_bytes())?;
             }
         }
         sink.write_all(b"}}\n")?;
         Ok(())
     }
     fn satisfy_one(&self) -> bool {
-        if let Some(b) = self.is_constant() {
-            return b;
-        }
-        if self.low().unwrap().satisfy_one() {
-            return true;
-        }
-        self.high().unwrap().satisfy_one()
+        find_path(self).is_some()
     }
     fn satisfy_all(&self) -> usize {
-        let mut count: HashMap<&Node, usize> = HashMap::new();
-        fn linear_count<'a>(count: &mut HashMap<&'a Node, usize>, node: &'a Node) -> usize {
+        let mut count: HashMap<Node, usize> = HashMap::new();
+        fn linear_count(count: &mut HashMap<Node, usize>, node: &Node) -> usize {
             if let Some(n) = count.get(node) {
                 return *n;
             }
             if let Some(b) = node.is_constant() {
                 return b as usize;
             }
-            let a = linear_count(count, node.low().unwrap());
-            let b = linear_count(count, node.high().unwrap());
+            let a = linear_count(count, &node.low().unwrap());
+            let b = linear_count(count, &node.high().unwrap());
             let c = a + b;
-            count.insert(node, c);
+            count.insert(node.clone(), c);
             c
         }
         linear_count(&mut count, self)
     }
 }
 
+/// return `true` if `map` keeps the order of the vars in the graph under `root`.
+pub(crate) fn preserves_order<N>(root: &N, map: impl Fn(usize) -> usize) -> bool
+where
+    N: DecisionDiagram<N> + DecisionDiagramNode,
+{
+    root.all_nodes()
+        .iter()
+        .filter_map(|n| n.var_index())
+        .sorted()
+        .dedup()
+        .map(map)
+        .tuple_windows()
+        .all(|(a, b)| a < b)
+}
+
+/// return the graph under `root` in which every var `vi` is replaced with `map(vi)`.
+/// Since `map` must keep the order of the vars, the result has the same shape.
+pub(crate) fn relabel<N: DecisionDiagramNode>(root: &N, map: &impl Fn(usize) -> usize) -> N {
+    fn aux<N: DecisionDiagramNode>(
+        node: &N,
+        map: &impl Fn(usize) -> usize,
+        memo: &mut HashMap<N, N>,
+    ) -> N {
+        if node.is_constant().is_some() {
+            return node.clone();
+        }
+        if let Some(n) = memo.get(node) {
+            return n.clone();
+        }
+        let low = aux(&node.low().unwrap(), map, memo);
+        let high = aux(&node.high().unwrap(), map, memo);
+        let n = N::new_var(map(node.var_index().unwrap()), low, high);
+        memo.insert(node.clone(), n.clone());
+        n
+    }
+    aux(root, map, &mut HashMap::new())
+}
+
+/// return the decisions on a path from `root` to the true terminal, trying
+/// `low` first. Since the subgraphs without such a path are remembered, every
+/// node is visited at most once.
+pub(crate) fn find_path<N: DecisionDiagramNode>(root: &N) -> Option<Vec<(usize, bool)>> {
+    fn aux<N: DecisionDiagramNode>(
+        node: &N,
+        dead: &mut HashSet<N>,
+        path: &mut Vec<(usize, bool)>,
+    ) -> bool {
+        if let Some(b) = node.is_constant() {
+            return b;
+        }
+        if dead.contains(node) {
+            return false;
+        }
+        let vi = node.var_index().unwrap();
+        for (b, child) in [(false, node.low().unwrap()), (true, node.high().unwrap())] {
+            path.push((vi, b));
+            if aux(&child, dead, path) {
+                return true;
+            }
+            path.pop();
+        }
+        dead.insert(node.clone());
+        false
+    }
+    let mut path: Vec<(usize, bool)> = Vec::new();
+    aux(root, &mut HashSet::new(), &mut path).then_some(path)
+}
+
+/// write the graph under `root` in graphvis format
+pub(crate) fn write_graph<N>(root: &N, mut sink: impl io::Write) -> io::Result<()>
+where
+    N: DecisionDiagram<N> + DecisionDiagramNode,
+{
+    sink.write_all(
+        b"digraph regexp {{
+  fontname=\"Helvetica,Arial,sans-serif\"
+  node [fontname=\"Helvetica,Arial,sans-serif\"]
+  edge [fontname=\"Helvetica,Arial,sans-serif\",color=blue]\n",
+    )?;
+    let nodes = root.all_nodes();
+    let mut index: HashMap<&N, usize> = HashMap::new();
+    let mut bools = (false, false);
+    for (i, n) in nodes.iter().enumerate() {
+        match n.is_constant() {
+            Some(false) => bools.0 |= true,
+            Some(true) => bools.1 |= true,
+            None => {
+                index.insert(n, i + 2);
+            }
+        }
+    }
+    let id = |n: &N| n.is_constant().map_or_else(|| index[n], |b| b as usize);
+    // nodes
+    if bools.0 {
+        sink.write_all(b"  0[style=filled,fillcolor=\"gray80\",label=\"false\",shape=\"box\"];\n")?;
+    }
+    if bools.1 {
+        sink.write_all(b"  1[style=filled,fillcolor=\"gray95\",label=\"true\",shape=\"box\"];\n")?;
+    }
+    for node in nodes.iter() {
+        if let Some(var_index) = node.var_index() {
+            let i = id(node);
+            sink.write_all(format!("  {i}[label=\"{var_index}\"];\n").as_bytes())?;
+        }
+    }
+    // edges
+    for node in nodes.iter() {
+        if let (Some(low), Some(high)) = (node.low(), node.high()) {
+            let i = id(node);
+            let j = id(&low);
+            let k = id(&high);
+            if j == k {
+                sink.write_all(format!("  {i} -> {j}[color=black,penwidth=2];\n").as_bytes())?;
+            } else {
+                sink.write_all(format!("  {i} -> {j}[color=red,style=\"dotted\"];\n").as_bytes())?;
+                sink.write_all(format!("  {i} -> {k}[color=blue];\n").as_bytes())?;
+            }
+        }
+    }
+    sink.write_all(b"}}\n")?;
+    Ok(())
+}
+
 impl DecisionDiagramNode for Node {
-    /// returns a new terminal node.
+    /// returns the edge to the shared terminal vertex.
     fn new_constant(b: bool) -> Node {
-        Rc::new(Vertex::Bool(b))
+        Node {
+            vertex: TERMINAL.with(|t| t.clone()),
+            complement: !b,
+        }
     }
-    /// returns a new non-terminal node.
+    /// returns the unique non-terminal node which has `var_index`, `low` and `high`.
+    /// Note: this doesn't eliminate redundant nodes, since the rule depends on the diagram type.
+    ///```
+    /// use ddir::node::Node;
+    /// use ddir::types::DecisionDiagramNode;
+    ///
+    /// let f = Node::new_constant(false);
+    /// let t = Node::new_constant(true);
+    /// let n = Node::new_var(2, f.clone(), t.clone());
+    /// assert_eq!(n, Node::new_var(2, f.clone(), t.clone()));
+    /// assert_ne!(n, Node::new_var(2, t.clone(), f.clone()));
+    /// assert_eq!(Node::new_var(2, f.clone(), f.clone()).var_index(), Some(2));
+    ///```
     fn new_var(var_index: usize, low: Node, high: Node) -> Node {
-        Rc::new(Vertex::Var {
-            var_index,
-            low,
-            high,
-        })
+        // keep the low edge regular by moving its complement attribute up
+        let complement = low.complement;
+        let (low, high) = if complement {
+            (low.flip(), high.flip())
+        } else {
+            (low, high)
+        };
+        Node {
+            vertex: UNIQUE_TABLE.with(|t| t.borrow_mut().get_or_insert(var_index, low, high)),
+            complement,
+        }
     }
     /// returns `None` if self is a non-terminal node.
     ///```
@@ -204,18 +484,18 @@ impl DecisionDiagramNode for Node {
     /// assert!(f.is_constant().is_some());
     ///```
     fn is_constant(&self) -> Option<bool> {
-        match **self {
-            Vertex::Bool(b) => Some(b),
+        match *self.vertex {
+            Vertex::Terminal => Some(!self.complement),
             Vertex::Var { .. } => None,
         }
     }
     fn unified_key(&self) -> usize {
-        match **self {
-            Vertex::Bool(b) => b as usize,
+        match *self.vertex {
+            Vertex::Terminal => !self.complement as usize,
             Vertex::Var { var_index, .. } => var_index + 2,
         }
     }
-    /// returns the number of nodes under self and self itself.
+    /// returns the decision var
     ///```
     /// use ddir::node::Node;
     /// use ddir::types::{DecisionDiagram, DecisionDiagramNode};
@@ -225,46 +505,44 @@ impl DecisionDiagramNode for Node {
     /// assert_eq!(n.var_index(), Some(2));
     ///```
     fn var_index(&self) -> Option<usize> {
-        match **self {
-            Vertex::Bool(_) => None,
+        match *self.vertex {
+            Vertex::Terminal => None,
             Vertex::Var { var_index, .. } => Some(var_index),
         }
     }
-    fn low(&self) -> Option<&Node> {
-        match **self {
-            Vertex::Bool(_) => None,
-            Vertex::Var { ref low, .. } => Some(low),
+    fn low(&self) -> Option<Node> {
+        match *self.vertex {
+            Vertex::Terminal => None,
+            Vertex::Var { ref low, .. } if self.complement => Some(low.flip()),
+            Vertex::Var { ref low, .. } => Some(low.clone()),
         }
     }
-    fn high(&self) -> Option<&Node> {
-        match **self {
-            Vertex::Bool(_) => None,
-            Vertex::Var { ref high, .. } => Some(high),
+    fn high(&self) -> Option<Node> {
+        match *self.vertex {
+            Vertex::Terminal => None,
+            Vertex::Var { ref high, .. } if self.complement => Some(high.flip()),
+            Vertex::Var { ref high, .. } => Some(high.clone()),
         }
     }
-    fn build_indexer(nodes: &[Self]) -> Indexer<Self> {
-        let mut node: HashMap<usize, Node> = HashMap::new();
-        let mut index: HashMap<Node, usize> = HashMap::new();
-        {
-            let f = Node::new_constant(false);
-            let t = Node::new_constant(true);
-            node.insert(0, f.clone());
-            index.insert(f, 0);
-            node.insert(1, t.clone());
-            index.insert(t, 1);
-        }
-        let mut i: usize = 1;
-        for root in nodes.iter() {
-            for n in root.all_nodes().iter() {
-                i += 1;
-                node.insert(i, (*n).clone());
-                index.insert(
-                    (*n).clone(),
-                    n.is_constant().map_or_else(|| i, |b| b as usize),
-                );
-            }
-        }
-        (index, node)
+    /// returns the complement edge in constant time.
+    ///```
+    /// use ddir::node::Node;
+    /// use ddir::types::DecisionDiagramNode;
+    ///
+    /// let f = Node::new_constant(false);
+    /// let t = Node::new_constant(true);
+    /// assert_eq!(f.complement(), Some(t.clone()));
+    /// let n = Node::new_var(2, f.clone(), t.clone());
+    /// assert_eq!(n.complement(), Some(Node::new_var(2, t, f)));
+    ///```
+    fn complement(&self) -> Option<Node> {
+        Some(self.flip())
+    }
+    fn is_complement(&self) -> bool {
+        self.complement
+    }
+    fn with_computed_table<R>(f: impl FnOnce(&mut ComputedTable<Node>) -> R) -> R {
+        COMPUTED_TABLE.with(|t| f(&mut t.borrow_mut()))
     }
 }
 
@@ -516,19 +794,58 @@ mod test {
     #[test]
     fn test_satisfy() {
         let f = Node::new_constant(false);
-        assert_eq!(f.satisfy_one(), false);
+        assert!(!f.satisfy_one());
         assert_eq!(f.satisfy_all(), 0);
         let t = Node::new_constant(true);
-        assert_eq!(t.satisfy_one(), true);
+        assert!(t.satisfy_one());
         assert_eq!(t.satisfy_all(), 1);
         let tt: Node = Node::new_var(2, t.clone(), t.clone());
-        assert_eq!(tt.satisfy_one(), true);
+        assert!(tt.satisfy_one());
         assert_eq!(tt.satisfy_all(), 2);
         let major = example::majority();
-        assert_eq!(major.satisfy_one(), true);
+        assert!(major.satisfy_one());
         assert_eq!(major.satisfy_all(), 3);
         let ind = example::independent_set();
-        assert_eq!(ind.satisfy_one(), true);
+        assert!(ind.satisfy_one());
         assert_eq!(ind.satisfy_all(), 18);
     }
+    #[test]
+    fn test_unique_table() {
+        let f = Node::new_constant(false);
+        let t = Node::new_constant(true);
+        assert_eq!(f, Node::new_constant(false));
+        assert_eq!(
+            f,
+            Node::new_var(2, Node::default(), t.clone()).low().unwrap()
+        );
+        let n = Node::new_var(2, f.clone(), t.clone());
+        assert_eq!(
+            Node::new_var(1, n.clone(), f.clone()),
+            Node::new_var(1, n, f)
+        );
+        // the full decision trees collapse to shared nodes
+        assert_eq!(example::majority().len(), 6);
+        assert_eq!(example::independent_set().len(), 22);
+    }
+    #[test]
+    fn test_complement_edge() {
+        let f = Node::new_constant(false);
+        let t = Node::new_constant(true);
+        assert_eq!(f, Node::default());
+        assert!(Rc::ptr_eq(&f.vertex, &t.vertex));
+        let n = Node::new_var(1, f.clone(), Node::new_var(2, t.clone(), f.clone()));
+        let m = Node::new_var(1, t.clone(), Node::new_var(2, f.clone(), t.clone()));
+        assert_eq!(n.complement(), Some(m.clone()));
+        assert!(Rc::ptr_eq(&n.vertex, &m.vertex));
+        assert!(n.is_complement() != m.is_complement());
+        assert_eq!(m.low(), Some(t.clone()));
+        assert_eq!(m.high().unwrap().low(), Some(f.clone()));
+        assert_eq!(n.satisfy_all(), 1);
+        assert_eq!(m.satisfy_all(), 2);
+        let mut gv: Vec<u8> = Vec::new();
+        m.write_as_gv(&mut gv).expect("fail to serialize");
+        let gv = String::from_utf8(gv).unwrap();
+        assert!(gv.contains("label=\"false\""));
+        assert!(gv.contains("label=\"true\""));
+    }
 }
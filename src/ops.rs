@@ -0,0 +1,158 @@
+//! Operator overloading for Decision Diagrams
+//!
+//! For BDDs, `&`, `|`, `^`, `-` and `!` are the conjunction, disjunction,
+//! exclusive or, difference (`a & !b`) and negation of Boolean functions.
+//! For ZDDs, `&`, `|`, `^` and `-` are the intersection, union, symmetric
+//! difference and difference of families of sets. ZDDs have no `!`, since the
+//! complement of a family depends on the universe.
+use {
+    crate::{
+        bdd::BDD,
+        types::{BinOp, DecisionDiagram, DecisionDiagramNode, ReducedDecisionDiagram},
+        zdd::ZDD,
+    },
+    std::ops::{
+        BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Div, DivAssign, Mul,
+        MulAssign, Not, Rem, RemAssign, Sub, SubAssign,
+    },
+};
+
+/// implement `$trait` and `$assign` for `$dd` by `$f: fn(&$dd, &$dd) -> $dd`
+macro_rules! impl_binop {
+    ($dd: ident, $trait: ident, $method: ident, $assign: ident, $assign_method: ident, $f: expr) => {
+        impl<N: DecisionDiagram<N> + DecisionDiagramNode> $trait<&$dd<N>> for &$dd<N> {
+            type Output = $dd<N>;
+            fn $method(self, rhs: &$dd<N>) -> $dd<N> {
+                $f(self, rhs)
+            }
+        }
+        impl<N: DecisionDiagram<N> + DecisionDiagramNode> $trait<&$dd<N>> for $dd<N> {
+            type Output = $dd<N>;
+            fn $method(self, rhs: &$dd<N>) -> $dd<N> {
+                $f(&self, rhs)
+            }
+        }
+        impl<N: DecisionDiagram<N> + DecisionDiagramNode> $trait<$dd<N>> for &$dd<N> {
+            type Output = $dd<N>;
+            fn $method(self, rhs: $dd<N>) -> $dd<N> {
+                $f(self, &rhs)
+            }
+        }
+        impl<N: DecisionDiagram<N> + DecisionDiagramNode> $trait<$dd<N>> for $dd<N> {
+            type Output = $dd<N>;
+            fn $method(self, rhs: $dd<N>) -> $dd<N> {
+                $f(&self, &rhs)
+            }
+        }
+        impl<N: DecisionDiagram<N> + DecisionDiagramNode> $assign<&$dd<N>> for $dd<N> {
+            fn $assign_method(&mut self, rhs: &$dd<N>) {
+                *self = $f(self, rhs);
+            }
+        }
+        impl<N: DecisionDiagram<N> + DecisionDiagramNode> $assign<$dd<N>> for $dd<N> {
+            fn $assign_method(&mut self, rhs: $dd<N>) {
+                *self = $f(self, &rhs);
+            }
+        }
+    };
+}
+
+impl_binop!(BDD, BitAnd, bitand, BitAndAssign, bitand_assign, BDD::and);
+impl_binop!(BDD, BitOr, bitor, BitOrAssign, bitor_assign, BDD::or);
+impl_binop!(BDD, BitXor, bitxor, BitXorAssign, bitxor_assign, BDD::xor);
+impl_binop!(BDD, Sub, sub, SubAssign, sub_assign, |f: &BDD<N>, g| {
+    f.apply(BinOp::Diff, g)
+});
+impl_binop!(
+    ZDD,
+    BitAnd,
+    bitand,
+    BitAndAssign,
+    bitand_assign,
+    ZDD::intersection
+);
+impl_binop!(ZDD, BitOr, bitor, BitOrAssign, bitor_assign, ZDD::union);
+impl_binop!(
+    ZDD,
+    BitXor,
+    bitxor,
+    BitXorAssign,
+    bitxor_assign,
+    ZDD::symmetric_difference
+);
+impl_binop!(ZDD, Sub, sub, SubAssign, sub_assign, ZDD::difference);
+impl_binop!(ZDD, Mul, mul, MulAssign, mul_assign, ZDD::join);
+impl_binop!(ZDD, Div, div, DivAssign, div_assign, ZDD::divide);
+impl_binop!(ZDD, Rem, rem, RemAssign, rem_assign, ZDD::remainder);
+
+impl<N: DecisionDiagram<N> + DecisionDiagramNode> Not for BDD<N> {
+    type Output = BDD<N>;
+    fn not(self) -> BDD<N> {
+        self.negate()
+    }
+}
+
+impl<N: DecisionDiagram<N> + DecisionDiagramNode> Not for &BDD<N> {
+    type Output = BDD<N>;
+    fn not(self) -> BDD<N> {
+        self.negate()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bdd::BDD,
+        node::{Node, example},
+        types::DecisionDiagram,
+        zdd::ZDD,
+    };
+
+    #[test]
+    fn test_bdd_ops() {
+        let x1x3: BDD<Node> = BDD::new_from(example::x1x3());
+        let x2x3: BDD<Node> = BDD::new_from(example::x2x3());
+        let major: BDD<Node> = BDD::new_from(example::majority());
+        assert_eq!(&x1x3 & &x2x3, x1x3.and(&x2x3));
+        assert_eq!(&x1x3 | &x2x3, x1x3.or(&x2x3));
+        assert_eq!(&x1x3 ^ &x2x3, x1x3.xor(&x2x3));
+        assert_eq!(&x1x3 - &x2x3, x1x3.and(&x2x3.negate()));
+        assert_eq!(!&major, major.negate());
+        assert_eq!(!!major.clone(), major);
+        // de Morgan
+        assert_eq!(!(&x1x3 & &major), !&x1x3 | !&major);
+        assert_eq!(x1x3.clone() | x2x3.clone(), &x2x3 | x1x3.clone());
+        let mut f = x1x3.clone();
+        f &= &x2x3;
+        f |= major.clone();
+        f ^= &major;
+        assert_eq!(f, (&x1x3 & &x2x3) - &major);
+        f -= x1x3;
+        assert_eq!(f.satisfy_all(), 0);
+    }
+    #[test]
+    fn test_zdd_ops() {
+        let ind: ZDD<Node> = ZDD::new_from(example::independent_set());
+        let krn: ZDD<Node> = ZDD::new_from(example::kernels());
+        assert_eq!(&ind & &krn, krn);
+        assert_eq!(&ind | &krn, ind);
+        assert_eq!((&ind - &krn) ^ krn.clone(), ind);
+        let mut rest = ind.clone();
+        rest -= &krn;
+        assert_eq!(rest.satisfy_all(), ind.satisfy_all() - krn.satisfy_all());
+        rest |= krn;
+        assert_eq!(rest, ind);
+        // (ab + ac + bc + d) = (b + c) * a + (bc + d)
+        let p: ZDD<Node> = ZDD::from_sets([vec![1, 2], vec![1, 3], vec![2, 3], vec![4]]);
+        let q = ZDD::from_sets([vec![2], vec![3]]);
+        let a = ZDD::from_sets([vec![1]]);
+        assert_eq!(&p / &q, a);
+        assert_eq!(&p % &q, ZDD::from_sets([vec![2, 3], vec![4]]));
+        assert_eq!((&q * &a) | (&p % &q), p);
+        let mut r = p.clone();
+        r /= q.clone();
+        r *= &q;
+        r %= a;
+        assert_eq!(r, ZDD::default());
+    }
+}
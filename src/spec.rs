@@ -0,0 +1,232 @@
+//! Top-down construction of Decision Diagrams from specifications
+//!
+//! Like TdZdd, a [`DdSpec`] describes a diagram by the states of its nodes and
+//! the transitions between them. The builder visits the states breadth-first
+//! in the var order, merges the equal states at each var, and builds the
+//! reduced diagram bottom-up, without the decision tree.
+use {
+    crate::types::DecisionDiagramNode,
+    std::{
+        collections::{BTreeMap, HashMap},
+        hash::Hash,
+    },
+};
+
+/// A node in a specification: a terminal, or a state at a var index
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Child<S> {
+    Terminal(bool),
+    Node(usize, S),
+}
+
+/// A specification of a diagram to build top-down.
+/// The vars which a path skips are don't-cares in a BDD, and are 0 in a ZDD.
+///```
+/// use ddir::bdd::BDD;
+/// use ddir::node::Node;
+/// use ddir::spec::{Child, DdSpec};
+/// use ddir::zdd::ZDD;
+///
+/// /// the subsets of `1..=n` which have `k` elements or less, counting the elements
+/// struct AtMost {
+///     n: usize,
+///     k: usize,
+/// }
+///
+/// impl DdSpec for AtMost {
+///     type State = usize;
+///     fn root(&self) -> Child<usize> {
+///         Child::Node(1, 0)
+///     }
+///     fn child(&self, count: &usize, var_index: usize, branch: bool) -> Child<usize> {
+///         let count = count + branch as usize;
+///         match (self.k < count, var_index == self.n) {
+///             (true, _) => Child::Terminal(false),
+///             (false, true) => Child::Terminal(true),
+///             (false, false) => Child::Node(var_index + 1, count),
+///         }
+///     }
+/// }
+///
+/// let spec = AtMost { n: 5, k: 2 };
+/// let family: ZDD<Node> = ZDD::from_spec(&spec);
+/// assert_eq!(family.count_sets(), 1 + 5 + 10);
+/// let function: BDD<Node> = BDD::from_spec(&spec);
+/// assert_eq!(function.count_models(1..=5), 16);
+///```
+pub trait DdSpec {
+    /// the state of a node, by which the equal nodes are merged
+    type State: Clone + Eq + Hash;
+    fn root(&self) -> Child<Self::State>;
+    /// return the child of the node with `state` at `var_index` on `branch`.
+    /// A child node must be at a larger var index.
+    fn child(&self, state: &Self::State, var_index: usize, branch: bool) -> Child<Self::State>;
+}
+
+/// the distinct states at a var index
+struct Level<S> {
+    states: Vec<S>,
+    index: HashMap<S, usize>,
+}
+
+impl<S: Clone + Eq + Hash> Level<S> {
+    fn new() -> Self {
+        Level {
+            states: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+    /// return the index of `state`, after adding it if it's new.
+    fn insert(&mut self, state: S) -> usize {
+        *self.index.entry(state).or_insert_with_key(|s| {
+            self.states.push(s.clone());
+            self.states.len() - 1
+        })
+    }
+}
+
+/// an edge to a terminal, or to the node at a var index and an index in the level
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Arc {
+    Terminal(bool),
+    Node(usize, usize),
+}
+
+/// return the root of the diagram specified by `spec`, in which the nodes are
+/// made by `new_node` from the var index and the low and high children.
+pub(crate) fn build<S, N>(spec: &S, new_node: impl Fn(usize, N, N) -> N) -> N
+where
+    S: DdSpec,
+    N: DecisionDiagramNode,
+{
+    let (root, state) = match spec.root() {
+        Child::Terminal(b) => return N::new_constant(b),
+        Child::Node(vi, state) => (vi, state),
+    };
+    let mut levels: BTreeMap<usize, Level<S::State>> = BTreeMap::new();
+    levels.entry(root).or_insert_with(Level::new).insert(state);
+    // the edges from the nodes at each var index, in the var order
+    let mut arcs: Vec<(usize, Vec<[Arc; 2]>)> = Vec::new();
+    while let Some((vi, level)) = levels.pop_first() {
+        let mut level_arcs = Vec::with_capacity(level.states.len());
+        for state in level.states.iter() {
+            let mut children = [Arc::Terminal(false); 2];
+            for (branch, child) in children.iter_mut().enumerate() {
+                *child = match spec.child(state, vi, branch == 1) {
+                    Child::Terminal(b) => Arc::Terminal(b),
+                    Child::Node(v, s) => {
+                        assert!(vi < v, "a child at var {v} under var {vi}");
+                        Arc::Node(v, levels.entry(v).or_insert_with(Level::new).insert(s))
+                    }
+                };
+            }
+            level_arcs.push(children);
+        }
+        arcs.push((vi, level_arcs));
+    }
+    let mut nodes: HashMap<usize, Vec<N>> = HashMap::new();
+    for (vi, level_arcs) in arcs.into_iter().rev() {
+        let node = |arc: &Arc| match arc {
+            Arc::Terminal(b) => N::new_constant(*b),
+            Arc::Node(v, i) => nodes[v][*i].clone(),
+        };
+        let level = level_arcs
+            .iter()
+            .map(|[low, high]| new_node(vi, node(low), node(high)))
+            .collect::<Vec<_>>();
+        nodes.insert(vi, level);
+    }
+    nodes[&root][0].clone()
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        crate::{
+            arena::ArenaNode,
+            bdd::{BDD, var},
+            node::{Node, example},
+            types::DecisionDiagram,
+            zdd::ZDD,
+        },
+    };
+
+    /// the independent sets of the cycle on the vertices `1..=n`.
+    /// A state is whether the vertex 1 and the previous vertex are in the set.
+    struct CycleIndependentSet(usize);
+
+    impl DdSpec for CycleIndependentSet {
+        type State = (bool, bool);
+        fn root(&self) -> Child<(bool, bool)> {
+            Child::Node(1, (false, false))
+        }
+        fn child(&self, state: &(bool, bool), vi: usize, branch: bool) -> Child<(bool, bool)> {
+            let (first, previous) = *state;
+            if branch && (previous || (vi == self.0 && first)) {
+                return Child::Terminal(false);
+            }
+            if vi == self.0 {
+                return Child::Terminal(true);
+            }
+            Child::Node(vi + 1, (first || (vi == 1 && branch), branch))
+        }
+    }
+
+    /// the subsets of `1..=n` which have exactly `k` elements, skipping the
+    /// rest of vars after `k` elements are chosen
+    struct Combination(usize, usize);
+
+    impl DdSpec for Combination {
+        type State = usize;
+        fn root(&self) -> Child<usize> {
+            match self.1 <= self.0 {
+                true if self.1 == 0 => Child::Terminal(true),
+                true => Child::Node(1, self.1),
+                false => Child::Terminal(false),
+            }
+        }
+        fn child(&self, rest: &usize, vi: usize, branch: bool) -> Child<usize> {
+            let rest = rest - branch as usize;
+            match rest {
+                0 => Child::Terminal(true),
+                _ if self.0 - vi < rest => Child::Terminal(false),
+                _ => Child::Node(vi + 1, rest),
+            }
+        }
+    }
+
+    #[test]
+    fn test_zdd_from_spec() {
+        let ind: ZDD<Node> = ZDD::from_spec(&CycleIndependentSet(6));
+        assert_eq!(ind, ZDD::new_from(example::independent_set()));
+        assert_eq!(ind.count_sets(), 18);
+        for n in 0..=6 {
+            for k in 0..=n + 1 {
+                let c: ZDD<Node> = ZDD::from_spec(&Combination(n, k));
+                assert_eq!(c, ZDD::k_subsets(1..=n, k), "C({n}, {k})");
+            }
+        }
+        let arena: ZDD<ArenaNode> = ZDD::from_spec(&CycleIndependentSet(30));
+        let node: ZDD<Node> = ZDD::from_spec(&CycleIndependentSet(30));
+        // the Lucas number L(30)
+        assert_eq!(arena.count_sets(), 1860498);
+        assert_eq!(node.count_sets(), 1860498);
+    }
+    #[test]
+    fn test_bdd_from_spec() {
+        let ind: BDD<Node> = BDD::from_spec(&CycleIndependentSet(6));
+        assert_eq!(ind, BDD::new_from(example::independent_set()));
+        // the skipped vars are don't-cares
+        let c: BDD<Node> = BDD::from_spec(&Combination(4, 2));
+        let at_least_two = (1..=4)
+            .flat_map(|i| (i + 1..=4).map(move |j| (i, j)))
+            .fold(BDD::new_from(Node::new_constant(false)), |f, (i, j)| {
+                f.or(&var(i).and(&var(j)))
+            });
+        assert_eq!(c, at_least_two);
+        assert_eq!(c.count_models(1..=4), 11);
+        let t: BDD<Node> = BDD::from_spec(&Combination(3, 0));
+        assert!(t.satisfy_one() && t.count_models(1..=3) == 8);
+    }
+}
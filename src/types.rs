@@ -1,16 +1,138 @@
 //! Types and traits
-use std::{
-    collections::{HashMap, HashSet},
-    hash::Hash,
-    io,
+use {
+    crate::cache::ComputedTable,
+    std::{
+        collections::{HashMap, HashSet},
+        hash::Hash,
+        io,
+    },
 };
 
-pub(crate) type BooleanOperator = (Box<dyn Fn(bool, bool) -> bool>, bool);
-pub(crate) type Indexer<N> = (HashMap<N, usize>, HashMap<usize, N>);
+/// A partial assignment from var indices to values. A var not in it is a don't-care.
+pub type Assignment = HashMap<usize, bool>;
+
+/// The 16 Boolean functions of two inputs.
+/// The discriminant is the truth table: bit `2 * a + b` holds `op(a, b)`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum BinOp {
+    False = 0b0000,
+    Nor = 0b0001,
+    /// `!a & b`
+    ConverseDiff = 0b0010,
+    /// `!a`
+    NotA = 0b0011,
+    /// `a & !b`
+    Diff = 0b0100,
+    /// `!b`
+    NotB = 0b0101,
+    Xor = 0b0110,
+    Nand = 0b0111,
+    And = 0b1000,
+    Xnor = 0b1001,
+    /// `b`
+    B = 0b1010,
+    /// `!a | b`
+    Implies = 0b1011,
+    /// `a`
+    A = 0b1100,
+    /// `a | !b`
+    ConverseImplies = 0b1101,
+    Or = 0b1110,
+    True = 0b1111,
+}
+
+/// The function of one input which remains after fixing an input of a [`BinOp`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Residual {
+    Constant(bool),
+    Identity,
+    Negation,
+}
+
+impl Residual {
+    /// return the function mapping `false` to `f` and `true` to `t`.
+    fn new(f: bool, t: bool) -> Residual {
+        match (f, t) {
+            (false, true) => Residual::Identity,
+            (true, false) => Residual::Negation,
+            (b, _) => Residual::Constant(b),
+        }
+    }
+}
+
+impl BinOp {
+    /// all operators in the order of their truth tables
+    pub const ALL: [BinOp; 16] = [
+        BinOp::False,
+        BinOp::Nor,
+        BinOp::ConverseDiff,
+        BinOp::NotA,
+        BinOp::Diff,
+        BinOp::NotB,
+        BinOp::Xor,
+        BinOp::Nand,
+        BinOp::And,
+        BinOp::Xnor,
+        BinOp::B,
+        BinOp::Implies,
+        BinOp::A,
+        BinOp::ConverseImplies,
+        BinOp::Or,
+        BinOp::True,
+    ];
+    /// return the operator whose truth table is the lower 4 bits of `table`.
+    pub fn from_truth_table(table: u8) -> BinOp {
+        BinOp::ALL[(table & 0b1111) as usize]
+    }
+    pub fn truth_table(self) -> u8 {
+        self as u8
+    }
+    ///```
+    /// use ddir::types::BinOp;
+    ///
+    /// assert!(BinOp::Implies.eval(false, true));
+    /// assert!(!BinOp::Implies.eval(true, false));
+    ///```
+    pub fn eval(self, a: bool, b: bool) -> bool {
+        (self as u8) >> (2 * a as u8 + b as u8) & 1 == 1
+    }
+    /// return the operator which swaps the inputs: `op.converse()(a, b) == op(b, a)`.
+    pub fn converse(self) -> BinOp {
+        let t = self as u8;
+        BinOp::from_truth_table(t & 0b1001 | (t & 0b0010) << 1 | (t & 0b0100) >> 1)
+    }
+    pub fn is_commutative(self) -> bool {
+        self.converse() == self
+    }
+    /// return `op(a, _)`
+    pub fn left(self, a: bool) -> Residual {
+        Residual::new(self.eval(a, false), self.eval(a, true))
+    }
+    /// return `op(_, b)`
+    pub fn right(self, b: bool) -> Residual {
+        Residual::new(self.eval(false, b), self.eval(true, b))
+    }
+    /// return `op(x, x)` as a function of `x`
+    pub fn diagonal(self) -> Residual {
+        Residual::new(self.eval(false, false), self.eval(true, true))
+    }
+    /// return the value `e` such that `op(e, x) == x == op(x, e)` for any `x`
+    pub fn identity(self) -> Option<bool> {
+        [false, true]
+            .into_iter()
+            .find(|e| self.left(*e) == Residual::Identity && self.right(*e) == Residual::Identity)
+    }
+    /// return the value `z` such that `op(z, x) == z == op(x, z)` for any `x`
+    pub fn absorbing(self) -> Option<bool> {
+        [false, true].into_iter().find(|z| {
+            self.left(*z) == Residual::Constant(*z) && self.right(*z) == Residual::Constant(*z)
+        })
+    }
+}
 
 pub trait DecisionDiagram<N: DecisionDiagramNode> {
     // return the hashset of all (non)terminal nodes in graph.
-    fn all_nodes(&self) -> HashSet<&N>;
+    fn all_nodes(&self) -> HashSet<N>;
     // return the number of (non)terminal nodes in graph.
     fn len(&self) -> usize;
     fn is_empty(&self) -> bool {
@@ -19,6 +141,7 @@ pub trait DecisionDiagram<N: DecisionDiagramNode> {
     // write the graph in graphvis format
     fn write_as_gv(&self, sink: impl io::Write) -> io::Result<()>;
     fn satisfy_one(&self) -> bool;
+    /// return the number of paths to the true terminal
     fn satisfy_all(&self) -> usize;
 }
 
@@ -33,18 +156,85 @@ pub trait DecisionDiagramNode: Clone + Default + Eq + Hash {
     /// return the decision var
     fn var_index(&self) -> Option<usize>;
     /// return the node element for the decision var == false
-    fn low(&self) -> Option<&Self>;
+    fn low(&self) -> Option<Self>;
     /// return the node element for the decision var == true
-    fn high(&self) -> Option<&Self>;
-    /// return indexer
-    fn build_indexer(nodes: &[Self]) -> Indexer<Self>;
+    fn high(&self) -> Option<Self>;
+    /// return the node for the negated function if it's available in constant time,
+    /// as the node type supports complement edges
+    fn complement(&self) -> Option<Self> {
+        None
+    }
+    /// return `true` if self is a complement edge
+    fn is_complement(&self) -> bool {
+        false
+    }
+    /// return the node made by rebuilding the nodes under self from the bottom
+    /// with `new_node`, which applies the reduction rules of a diagram type.
+    fn rebuild(&self, new_node: &impl Fn(usize, Self, Self) -> Self) -> Self {
+        fn aux<N: DecisionDiagramNode>(
+            node: &N,
+            new_node: &impl Fn(usize, N, N) -> N,
+            memo: &mut HashMap<N, N>,
+        ) -> N {
+            if node.is_constant().is_some() {
+                return node.clone();
+            }
+            if let Some(n) = memo.get(node) {
+                return n.clone();
+            }
+            let low = aux(&node.low().unwrap(), new_node, memo);
+            let high = aux(&node.high().unwrap(), new_node, memo);
+            let n = new_node(node.var_index().unwrap(), low, high);
+            memo.insert(node.clone(), n.clone());
+            n
+        }
+        aux(self, new_node, &mut HashMap::new())
+    }
+    /// call `f` with the computed table shared by all diagrams in this thread
+    fn with_computed_table<R>(f: impl FnOnce(&mut ComputedTable<Self>) -> R) -> R;
 }
 
 pub trait ReducedDecisionDiagram {
     /// convert the current graph to one which is a reduced diagram
     fn reduce(&mut self);
     /// return a new graph generated by apply `op` to this and the other graph
-    fn apply(&self, op: Box<dyn Fn(bool, bool) -> bool>, unit: bool, _other: &Self) -> Self;
+    fn apply(&self, op: BinOp, other: &Self) -> Self;
     /// compose two diagrams
     fn compose(&self, other: &Self, at: usize) -> Self;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_binop() {
+        for op in BinOp::ALL {
+            assert_eq!(BinOp::from_truth_table(op.truth_table()), op);
+            assert_eq!(op.converse().converse(), op);
+            for a in [false, true] {
+                for b in [false, true] {
+                    assert_eq!(op.converse().eval(a, b), op.eval(b, a));
+                }
+            }
+        }
+        assert_eq!(BinOp::And.truth_table(), 0b1000);
+        assert_eq!(BinOp::Or.truth_table(), 0b1110);
+        assert_eq!(BinOp::A.truth_table(), 0b1100);
+        assert_eq!(BinOp::Diff.converse(), BinOp::ConverseDiff);
+        assert_eq!(
+            BinOp::ALL.iter().filter(|op| op.is_commutative()).count(),
+            8
+        );
+        assert_eq!(BinOp::And.identity(), Some(true));
+        assert_eq!(BinOp::And.absorbing(), Some(false));
+        assert_eq!(BinOp::Or.identity(), Some(false));
+        assert_eq!(BinOp::Or.absorbing(), Some(true));
+        assert_eq!(BinOp::Xor.identity(), Some(false));
+        assert_eq!(BinOp::Xor.absorbing(), None);
+        assert_eq!(BinOp::Xor.left(true), Residual::Negation);
+        assert_eq!(BinOp::Nand.left(false), Residual::Constant(true));
+        assert_eq!(BinOp::Diff.right(false), Residual::Identity);
+        assert_eq!(BinOp::Xnor.diagonal(), Residual::Constant(true));
+    }
+}
@@ -2,10 +2,12 @@
 
 use {
     crate::{
-        node::{Node, Vertex},
-        types::{
-            BooleanOperator, DecisionDiagram, DecisionDiagramNode, Indexer, ReducedDecisionDiagram,
-        },
+        cache::{Operation, operand_order},
+        count::{Count, count_paths},
+        iter::Sets,
+        node::{find_path, preserves_order, relabel, write_graph},
+        spec::{DdSpec, build},
+        types::{BinOp, DecisionDiagram, DecisionDiagramNode, ReducedDecisionDiagram, Residual},
     },
     itertools::Itertools,
     std::{
@@ -21,26 +23,789 @@ pub struct ZDD<N> {
     phantom: PhantomData<()>,
 }
 
-impl ZDD<Node> {
-    pub fn new_from(graph: Node) -> ZDD<Node> {
+impl<N: DecisionDiagram<N> + DecisionDiagramNode> ZDD<N> {
+    pub fn new_from(graph: N) -> Self {
         let mut zdd = ZDD {
-            graph: graph.clone(),
+            graph,
             ..Default::default()
         };
         zdd.reduce();
         zdd
     }
+    /// return the ZDD specified by `spec`, which is built top-down without
+    /// the decision tree. See [`DdSpec`].
+    pub fn from_spec(spec: &impl DdSpec) -> Self {
+        ZDD {
+            graph: build(spec, ZDD::new_node),
+            ..Default::default()
+        }
+    }
+    /// return the empty family.
+    pub fn empty() -> Self {
+        ZDD::default()
+    }
+    /// return the family which has only the empty set.
+    ///```
+    /// use ddir::node::{example, Node};
+    /// use ddir::zdd::ZDD;
+    ///
+    /// let base: ZDD<Node> = ZDD::base();
+    /// let major = base.change(1).change(2)
+    ///     .union(&base.change(1).change(3))
+    ///     .union(&base.change(2).change(3));
+    /// assert_eq!(major, ZDD::new_from(example::majority()));
+    ///```
+    pub fn base() -> Self {
+        ZDD {
+            graph: N::new_constant(true),
+            ..Default::default()
+        }
+    }
+    /// return the family of `sets`.
+    ///```
+    /// use ddir::zdd::ZDD;
+    /// use ddir::node::Node;
+    ///
+    /// let family: ZDD<Node> = ZDD::from_sets([vec![1, 2], vec![], vec![3]]);
+    /// assert_eq!(family.count_sets(), 3);
+    /// assert_eq!(family.satisfy_one_set(), Some(vec![]));
+    ///```
+    pub fn from_sets<S: IntoIterator<Item = usize>>(sets: impl IntoIterator<Item = S>) -> Self {
+        let graph = sets
+            .into_iter()
+            .fold(N::new_constant(false), |family, set| {
+                let f = N::new_constant(false);
+                let single = set
+                    .into_iter()
+                    .sorted_by(|a, b| b.cmp(a))
+                    .dedup()
+                    .fold(N::new_constant(true), |n, vi| N::new_var(vi, f.clone(), n));
+                ZDD::apply_node(BinOp::Or, &family, &single)
+            });
+        ZDD {
+            graph,
+            ..Default::default()
+        }
+    }
+    /// return the family of all `k`-subsets of `universe`.
+    ///```
+    /// use ddir::zdd::ZDD;
+    /// use ddir::node::Node;
+    ///
+    /// let pairs: ZDD<Node> = ZDD::k_subsets(1..=4, 2);
+    /// assert_eq!(pairs.count_sets(), 6);
+    /// let power = (0..=4).fold(ZDD::empty(), |f, k| f.union(&ZDD::k_subsets(1..=4, k)));
+    /// assert_eq!(power.count_sets(), 16);
+    /// assert_eq!(power.exactly(2), pairs);
+    ///```
+    pub fn k_subsets(universe: impl IntoIterator<Item = usize>, k: usize) -> Self {
+        let vars = universe
+            .into_iter()
+            .sorted_by(|a, b| b.cmp(a))
+            .dedup()
+            .collect::<Vec<_>>();
+        if vars.len() < k {
+            return ZDD::empty();
+        }
+        // families[j] holds the j-subsets of the vars processed so far
+        let mut families = vec![N::new_constant(false); k + 1];
+        families[0] = N::new_constant(true);
+        for vi in vars {
+            for j in (0..=k).rev() {
+                let high = match j {
+                    0 => N::new_constant(false),
+                    _ => families[j - 1].clone(),
+                };
+                families[j] = ZDD::new_node(vi, families[j].clone(), high);
+            }
+        }
+        ZDD {
+            graph: families.swap_remove(k),
+            ..Default::default()
+        }
+    }
+    /// return the sets in self or `other`.
+    /// Like the other set operations, it follows Minato's recursion, in which
+    /// a var missing in a family leads to the empty family on the high branch.
+    /// All of them share the entries in the computed table.
+    pub fn union(&self, other: &Self) -> Self {
+        self.apply(BinOp::Or, other)
+    }
+    /// return the sets in both self and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        self.apply(BinOp::And, other)
+    }
+    /// return the sets in self but not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        self.apply(BinOp::Diff, other)
+    }
+    /// return the sets in exactly one of self and `other`.
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        self.apply(BinOp::Xor, other)
+    }
+    /// return the sets which contain `var_index`, with `var_index` removed.
+    /// It's also called subset1.
+    ///```
+    /// use ddir::node::Node;
+    /// use ddir::zdd::ZDD;
+    ///
+    /// let p: ZDD<Node> = ZDD::from_sets([vec![1, 2], vec![2, 3], vec![3]]);
+    /// assert_eq!(p.onset(2), ZDD::from_sets([vec![1], vec![3]]));
+    /// assert_eq!(p.offset(2), ZDD::from_sets([vec![3]]));
+    /// assert_eq!(p.change(3), ZDD::from_sets([vec![1, 2, 3], vec![2], vec![]]));
+    ///```
+    pub fn onset(&self, var_index: usize) -> Self {
+        ZDD {
+            graph: ZDD::subset_node(&self.graph, var_index, true),
+            ..Default::default()
+        }
+    }
+    /// return the sets which don't contain `var_index`. It's also called subset0.
+    pub fn offset(&self, var_index: usize) -> Self {
+        ZDD {
+            graph: ZDD::subset_node(&self.graph, var_index, false),
+            ..Default::default()
+        }
+    }
+    /// return the family made by toggling `var_index` in every set.
+    pub fn change(&self, var_index: usize) -> Self {
+        ZDD {
+            graph: ZDD::change_node(&self.graph, var_index),
+            ..Default::default()
+        }
+    }
+    /// return the sets which aren't a proper subset of any set in the family.
+    ///```
+    /// use ddir::node::example;
+    /// use ddir::zdd::ZDD;
+    ///
+    /// // the kernels of a graph are its maximal independent sets
+    /// let ind = ZDD::new_from(example::independent_set());
+    /// assert_eq!(ind.maximal(), ZDD::new_from(example::kernels()));
+    ///```
+    pub fn maximal(&self) -> Self {
+        ZDD {
+            graph: ZDD::extremal_node(Operation::ZddMaximal, &self.graph),
+            ..Default::default()
+        }
+    }
+    /// return the sets which aren't a proper superset of any set in the family.
+    pub fn minimal(&self) -> Self {
+        ZDD {
+            graph: ZDD::extremal_node(Operation::ZddMinimal, &self.graph),
+            ..Default::default()
+        }
+    }
+    /// return the sets which contain no set in `other`.
+    pub fn nonsup(&self, other: &Self) -> Self {
+        ZDD {
+            graph: ZDD::filter_node(Operation::ZddNonSup, &self.graph, &other.graph),
+            ..Default::default()
+        }
+    }
+    /// return the sets which are contained in no set in `other`.
+    pub fn nonsub(&self, other: &Self) -> Self {
+        ZDD {
+            graph: ZDD::filter_node(Operation::ZddNonSub, &self.graph, &other.graph),
+            ..Default::default()
+        }
+    }
+    /// return the minimal hitting sets, or the transversal, of the family.
+    /// A hitting set shares an element with every set in the family.
+    ///```
+    /// use ddir::zdd::ZDD;
+    /// use ddir::node::Node;
+    ///
+    /// let cuts: ZDD<Node> = ZDD::from_sets([vec![1, 2], vec![2, 3]]);
+    /// assert_eq!(cuts.minimal_hitting_sets(), ZDD::from_sets([vec![2], vec![1, 3]]));
+    /// assert_eq!(cuts.minimal_hitting_sets().minimal_hitting_sets(), cuts);
+    ///```
+    pub fn minimal_hitting_sets(&self) -> Self {
+        ZDD {
+            graph: ZDD::hitting_sets_node(&self.graph),
+            ..Default::default()
+        }
+    }
+    /// Knuth's recursion: with `F = F0 + v F1`,
+    /// `hit(F) = hit(F0 + F1) + v nonsup(hit(F0), hit(F0 + F1))`.
+    fn hitting_sets_node(node: &N) -> N {
+        if let Some(b) = node.is_constant() {
+            // every set hits no set, and no set hits the empty set
+            return N::new_constant(!b);
+        }
+        let f = N::new_constant(false);
+        let hash_key = (Operation::ZddHittingSets, node.clone(), f.clone(), f);
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let low = node.low().unwrap();
+        let high = node.high().unwrap();
+        let both = ZDD::hitting_sets_node(&ZDD::apply_node(BinOp::Or, &low, &high));
+        let n = ZDD::new_node(
+            node.var_index().unwrap(),
+            both.clone(),
+            ZDD::filter_node(Operation::ZddNonSup, &ZDD::hitting_sets_node(&low), &both),
+        );
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// the maximal or minimal sets, chosen by `operation`. With `F = F0 + v F1`,
+    /// `max(F) = nonsub(max(F0), F1) + v max(F1)` and
+    /// `min(F) = min(F0) + v nonsup(min(F1), F0)`.
+    fn extremal_node(operation: Operation, node: &N) -> N {
+        if node.is_constant().is_some() {
+            return node.clone();
+        }
+        let f = N::new_constant(false);
+        let hash_key = (operation, node.clone(), f.clone(), f);
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let low = node.low().unwrap();
+        let high = node.high().unwrap();
+        let (low, high) = match operation {
+            Operation::ZddMaximal => (
+                ZDD::filter_node(
+                    Operation::ZddNonSub,
+                    &ZDD::extremal_node(operation, &low),
+                    &high,
+                ),
+                ZDD::extremal_node(operation, &high),
+            ),
+            Operation::ZddMinimal => (
+                ZDD::extremal_node(operation, &low),
+                ZDD::filter_node(
+                    Operation::ZddNonSup,
+                    &ZDD::extremal_node(operation, &high),
+                    &low,
+                ),
+            ),
+            _ => unreachable!(),
+        };
+        let n = ZDD::new_node(node.var_index().unwrap(), low, high);
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// the sets in `p` which contain no set in `q`, or which are contained in
+    /// no set in `q`, chosen by `operation`. With `P = P0 + v P1` and `Q = Q0 + v Q1`,
+    /// `nonsup(P, Q) = nonsup(P0, Q0) + v nonsup(nonsup(P1, Q1), Q0)` and
+    /// `nonsub(P, Q) = nonsub(nonsub(P0, Q0), Q1) + v nonsub(P1, Q1)`.
+    fn filter_node(operation: Operation, p: &N, q: &N) -> N {
+        let f = N::new_constant(false);
+        match (p.is_constant(), q.is_constant()) {
+            (Some(false), _) => return f,
+            (_, Some(false)) => return p.clone(),
+            _ if p == q => return f,
+            // every set contains the empty set
+            (_, Some(true)) if operation == Operation::ZddNonSup => return f,
+            // the empty set is contained in every set
+            (Some(true), _) if operation == Operation::ZddNonSub => return f,
+            (Some(true), Some(true)) => unreachable!(),
+            _ => (),
+        }
+        let hash_key = (operation, p.clone(), q.clone(), f.clone());
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let key = [p, q]
+            .iter()
+            .map(|n| n.unified_key())
+            .filter(|k| 1 < *k)
+            .min()
+            .unwrap();
+        let cofactors = |n: &N| {
+            if n.unified_key() == key {
+                (n.low().unwrap(), n.high().unwrap())
+            } else {
+                (n.clone(), f.clone())
+            }
+        };
+        let (p0, p1) = cofactors(p);
+        let (q0, q1) = cofactors(q);
+        let filter = |a: &N, b: &N| ZDD::filter_node(operation, a, b);
+        let (low, high) = match operation {
+            Operation::ZddNonSup => (filter(&p0, &q0), filter(&filter(&p1, &q1), &q0)),
+            Operation::ZddNonSub => (filter(&filter(&p0, &q0), &q1), filter(&p1, &q1)),
+            _ => unreachable!(),
+        };
+        let n = ZDD::new_node(key - 2, low, high);
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return the sets which have exactly `k` elements.
+    pub fn exactly(&self, k: usize) -> Self {
+        ZDD {
+            graph: ZDD::size_node(&self.graph, k, k),
+            ..Default::default()
+        }
+    }
+    /// return the sets which have `k` elements or less.
+    pub fn at_most(&self, k: usize) -> Self {
+        ZDD {
+            graph: ZDD::size_node(&self.graph, 0, k),
+            ..Default::default()
+        }
+    }
+    /// return the sets which have `k` elements or more.
+    pub fn at_least(&self, k: usize) -> Self {
+        ZDD {
+            graph: ZDD::size_node(&self.graph, k, usize::MAX),
+            ..Default::default()
+        }
+    }
+    /// return the sets in `node` whose sizes are in `min..=max`.
+    /// `max == usize::MAX` means no upper bound.
+    fn size_node(node: &N, min: usize, max: usize) -> N {
+        if let Some(b) = node.is_constant() {
+            return N::new_constant(b && min == 0);
+        }
+        if min == 0 && max == usize::MAX {
+            return node.clone();
+        }
+        let f = N::new_constant(false);
+        let hash_key = (Operation::ZddSize(min, max), node.clone(), f.clone(), f);
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let high = match max {
+            0 => N::new_constant(false),
+            usize::MAX => ZDD::size_node(&node.high().unwrap(), min.saturating_sub(1), max),
+            _ => ZDD::size_node(&node.high().unwrap(), min.saturating_sub(1), max - 1),
+        };
+        let n = ZDD::new_node(
+            node.var_index().unwrap(),
+            ZDD::size_node(&node.low().unwrap(), min, max),
+            high,
+        );
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return the join, or product, `{p | q : p in self, q in other}`.
+    ///```
+    /// use ddir::node::Node;
+    /// use ddir::zdd::ZDD;
+    ///
+    /// let p: ZDD<Node> = ZDD::from_sets([vec![1], vec![2]]);
+    /// let q = ZDD::from_sets([vec![], vec![1, 3]]);
+    /// assert_eq!(p.join(&q), ZDD::from_sets([vec![1], vec![2], vec![1, 3], vec![1, 2, 3]]));
+    ///```
+    pub fn join(&self, other: &Self) -> Self {
+        ZDD {
+            graph: ZDD::product_node(Operation::ZddJoin, &self.graph, &other.graph),
+            ..Default::default()
+        }
+    }
+    /// return the meet `{p & q : p in self, q in other}`.
+    pub fn meet(&self, other: &Self) -> Self {
+        ZDD {
+            graph: ZDD::product_node(Operation::ZddMeet, &self.graph, &other.graph),
+            ..Default::default()
+        }
+    }
+    /// return `{p | q : p in self, q in other, p & q is empty}`.
+    pub fn disjoint_product(&self, other: &Self) -> Self {
+        ZDD {
+            graph: ZDD::product_node(Operation::ZddDisjointProduct, &self.graph, &other.graph),
+            ..Default::default()
+        }
+    }
+    /// return the weak division, the largest family `r` such that `r * other`
+    /// is a subset of self and every set in `r` is disjoint with the sets in `other`.
+    /// `other` must not be the empty family.
+    ///```
+    /// use ddir::node::Node;
+    /// use ddir::zdd::ZDD;
+    ///
+    /// // (ab + ac + bc + d) / (b + c) = a
+    /// let p: ZDD<Node> = ZDD::from_sets([vec![1, 2], vec![1, 3], vec![2, 3], vec![4]]);
+    /// let q = ZDD::from_sets([vec![2], vec![3]]);
+    /// assert_eq!(p.divide(&q), ZDD::from_sets([vec![1]]));
+    /// assert_eq!(p.remainder(&q), ZDD::from_sets([vec![2, 3], vec![4]]));
+    ///```
+    pub fn divide(&self, other: &Self) -> Self {
+        ZDD {
+            graph: ZDD::divide_node(&self.graph, &other.graph),
+            ..Default::default()
+        }
+    }
+    /// return the remainder of the weak division, `self - (self / other) * other`.
+    pub fn remainder(&self, other: &Self) -> Self {
+        self.difference(&self.divide(other).join(other))
+    }
+    /// the join, the meet and the disjoint product, chosen by `operation`
+    fn product_node(operation: Operation, p: &N, q: &N) -> N {
+        let f = N::new_constant(false);
+        let t = N::new_constant(true);
+        match (p.is_constant(), q.is_constant()) {
+            (Some(false), _) | (_, Some(false)) => return f,
+            (Some(true), _) if operation == Operation::ZddMeet => return t,
+            (_, Some(true)) if operation == Operation::ZddMeet => return t,
+            (Some(true), _) => return q.clone(),
+            (_, Some(true)) => return p.clone(),
+            _ => (),
+        }
+        let (p, q) = if operand_order(q) < operand_order(p) {
+            (q, p)
+        } else {
+            (p, q)
+        };
+        let hash_key = (operation, p.clone(), q.clone(), f.clone());
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let key = p.unified_key().min(q.unified_key());
+        let cofactors = |n: &N| {
+            if n.unified_key() == key {
+                (n.low().unwrap(), n.high().unwrap())
+            } else {
+                (n.clone(), f.clone())
+            }
+        };
+        let (p0, p1) = cofactors(p);
+        let (q0, q1) = cofactors(q);
+        let product = |a: &N, b: &N| ZDD::product_node(operation, a, b);
+        let union = |a: N, b: N| ZDD::apply_node(BinOp::Or, &a, &b);
+        let (low, high) = match operation {
+            Operation::ZddJoin => (
+                product(&p0, &q0),
+                union(
+                    union(product(&p0, &q1), product(&p1, &q0)),
+                    product(&p1, &q1),
+                ),
+            ),
+            Operation::ZddMeet => (
+                union(
+                    union(product(&p0, &q0), product(&p0, &q1)),
+                    product(&p1, &q0),
+                ),
+                product(&p1, &q1),
+            ),
+            Operation::ZddDisjointProduct => (
+                product(&p0, &q0),
+                union(product(&p0, &q1), product(&p1, &q0)),
+            ),
+            _ => unreachable!(),
+        };
+        let n = ZDD::new_node(key - 2, low, high);
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    fn divide_node(p: &N, q: &N) -> N {
+        match (p.is_constant(), q.is_constant()) {
+            (_, Some(false)) => panic!("division by the empty family"),
+            (_, Some(true)) => return p.clone(),
+            (Some(_), _) => return N::new_constant(false),
+            _ if p == q => return N::new_constant(true),
+            _ => (),
+        }
+        let f = N::new_constant(false);
+        let hash_key = (Operation::ZddDivide, p.clone(), q.clone(), f.clone());
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        // Q = Q0 + v Q1, and P / Q = (P1 / Q1) & (P0 / Q0)
+        let vi = q.var_index().unwrap();
+        let (q0, q1) = (q.low().unwrap(), q.high().unwrap());
+        let p1 = ZDD::subset_node(p, vi, true);
+        let mut n = ZDD::divide_node(&p1, &q1);
+        if q0 != f && n != f {
+            let p0 = ZDD::subset_node(p, vi, false);
+            n = ZDD::apply_node(BinOp::And, &n, &ZDD::divide_node(&p0, &q0));
+        }
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return a set in the family, in ascending order of var indices.
+    /// It takes time linear in the size of the diagram.
+    ///```
+    /// use ddir::node::example;
+    /// use ddir::zdd::ZDD;
+    ///
+    /// let major = ZDD::new_from(example::majority());
+    /// assert_eq!(major.satisfy_one_set(), Some(vec![2, 3]));
+    ///```
+    pub fn satisfy_one_set(&self) -> Option<Vec<usize>> {
+        find_path(&self.graph).map(|path| {
+            path.into_iter()
+                .filter(|(_, b)| *b)
+                .map(|(vi, _)| vi)
+                .collect()
+        })
+    }
+    /// return a lazy iterator over the member sets.
+    ///```
+    /// use ddir::node::example;
+    /// use ddir::zdd::ZDD;
+    ///
+    /// let major = ZDD::new_from(example::majority());
+    /// let sets = major.sets().collect::<Vec<_>>();
+    /// assert_eq!(sets, vec![vec![2, 3], vec![1, 3], vec![1, 2]]);
+    ///```
+    pub fn sets(&self) -> Sets<N> {
+        Sets::new(&self.graph)
+    }
+    /// return the family in which every element `vi` in `map` is renamed to `map[vi]`.
+    /// Sets which become the same are merged. It just relabels the nodes if the
+    /// renaming keeps the order of the vars.
+    ///```
+    /// use std::collections::HashMap;
+    /// use ddir::node::example;
+    /// use ddir::zdd::ZDD;
+    ///
+    /// let major = ZDD::new_from(example::majority());
+    /// let mut sets = major.rename(&HashMap::from([(1, 4)])).sets().collect::<Vec<_>>();
+    /// sets.sort();
+    /// assert_eq!(sets, vec![vec![2, 3], vec![2, 4], vec![3, 4]]);
+    ///```
+    pub fn rename(&self, map: &HashMap<usize, usize>) -> Self {
+        let rename = |vi: usize| map.get(&vi).copied().unwrap_or(vi);
+        if preserves_order(&self.graph, rename) {
+            return ZDD {
+                graph: relabel(&self.graph, &rename),
+                ..Default::default()
+            };
+        }
+        fn aux<N: DecisionDiagram<N> + DecisionDiagramNode>(
+            node: &N,
+            rename: &impl Fn(usize) -> usize,
+            memo: &mut HashMap<N, N>,
+        ) -> N {
+            if node.is_constant().is_some() {
+                return node.clone();
+            }
+            if let Some(n) = memo.get(node) {
+                return n.clone();
+            }
+            let low = aux(&node.low().unwrap(), rename, memo);
+            let high = ZDD::insert(
+                &aux(&node.high().unwrap(), rename, memo),
+                rename(node.var_index().unwrap()),
+            );
+            let n = ZDD::apply_node(BinOp::Or, &low, &high);
+            memo.insert(node.clone(), n.clone());
+            n
+        }
+        ZDD {
+            graph: aux(&self.graph, &rename, &mut HashMap::new()),
+            ..Default::default()
+        }
+    }
+    /// return the family in which every element `vi` is renamed to `permutation[vi]`.
+    /// The elements out of `permutation` are kept.
+    pub fn permute(&self, permutation: &[usize]) -> Self {
+        self.rename(&permutation.iter().copied().enumerate().collect())
+    }
+    /// return the family made by adding `var_index` to every set in `node`.
+    fn insert(node: &N, var_index: usize) -> N {
+        let f = N::new_constant(false);
+        let key = var_index + 2;
+        if key < node.unified_key() || node.is_constant().is_some() {
+            return ZDD::new_node(var_index, f, node.clone());
+        }
+        let hash_key = (Operation::ZddInsert(var_index), node.clone(), f.clone(), f);
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let low = node.low().unwrap();
+        let high = node.high().unwrap();
+        let n = if key == node.unified_key() {
+            let both = ZDD::apply_node(BinOp::Or, &low, &high);
+            ZDD::new_node(var_index, N::new_constant(false), both)
+        } else {
+            ZDD::new_node(
+                node.var_index().unwrap(),
+                ZDD::insert(&low, var_index),
+                ZDD::insert(&high, var_index),
+            )
+        };
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return the number of sets in the family.
+    pub fn count_sets(&self) -> usize {
+        self.count_sets_as()
+    }
+    /// return the number of sets in the family as a `C`.
+    /// Use [`BigUint`](crate::count::BigUint) for the exact count of a huge family.
+    pub fn count_sets_as<C: Count>(&self) -> C {
+        count_paths::<N, C>(&self.graph, &|_, _| 0)
+    }
+    /// return the number of sets of each size, indexed by the size.
+    /// It has no trailing zeros, so the empty family results in an empty vector.
+    ///```
+    /// use ddir::node::{example, Node};
+    /// use ddir::zdd::ZDD;
+    ///
+    /// let ind: ZDD<Node> = ZDD::new_from(example::independent_set());
+    /// assert_eq!(ind.size_distribution(), vec![1, 6, 9, 2]);
+    ///```
+    pub fn size_distribution(&self) -> Vec<usize> {
+        self.size_distribution_as()
+    }
+    /// return the number of sets of each size as `C`s, indexed by the size.
+    pub fn size_distribution_as<C: Count>(&self) -> Vec<C> {
+        fn distribution<N: DecisionDiagramNode, C: Count>(
+            node: &N,
+            memo: &mut HashMap<N, Vec<C>>,
+        ) -> Vec<C> {
+            match node.is_constant() {
+                Some(false) => return Vec::new(),
+                Some(true) => return vec![C::one()],
+                None => (),
+            }
+            if let Some(d) = memo.get(node) {
+                return d.clone();
+            }
+            let low = distribution(&node.low().unwrap(), memo);
+            let high = distribution(&node.high().unwrap(), memo);
+            // the sets on the high branch have one more element
+            let d = (0..low.len().max(high.len() + 1))
+                .map(|i| {
+                    let l = low.get(i).cloned().unwrap_or_else(C::zero);
+                    match i.checked_sub(1).and_then(|j| high.get(j)) {
+                        Some(h) => l.add(h),
+                        None => l,
+                    }
+                })
+                .collect::<Vec<_>>();
+            memo.insert(node.clone(), d.clone());
+            d
+        }
+        distribution(&self.graph, &mut HashMap::new())
+    }
+    /// return the family of the sets `s` such that `op(s in v1, s in v2)`,
+    /// where `op(false, false)` must be `false`.
+    fn apply_node(op: BinOp, v1: &N, v2: &N) -> N {
+        // the terminal cases, in which the residual is the empty family or the other operand
+        let residual = |r: Residual, n: &N| match r {
+            Residual::Constant(false) => N::new_constant(false),
+            Residual::Identity => n.clone(),
+            _ => unreachable!(),
+        };
+        match (v1.is_constant(), v2.is_constant()) {
+            (Some(a), Some(b)) => return N::new_constant(op.eval(a, b)),
+            (Some(false), _) => return residual(op.left(false), v2),
+            (_, Some(false)) => return residual(op.right(false), v1),
+            _ if v1 == v2 => return residual(op.diagonal(), v1),
+            _ => (),
+        }
+        let (op, v1, v2) = if operand_order(v2) < operand_order(v1) {
+            (op.converse(), v2, v1)
+        } else {
+            (op, v1, v2)
+        };
+        let hash_key = (
+            Operation::ZddApply(op),
+            v1.clone(),
+            v2.clone(),
+            N::new_constant(false),
+        );
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n; // have already evaluated
+        }
+        let key = [v1, v2]
+            .iter()
+            .map(|n| n.unified_key())
+            .filter(|k| 1 < *k)
+            .min()
+            .unwrap();
+        // a var missing in a family means that no set in it contains the var.
+        let cofactors = |n: &N| {
+            if n.unified_key() == key {
+                (n.low().unwrap(), n.high().unwrap())
+            } else {
+                (n.clone(), N::new_constant(false))
+            }
+        };
+        let (vlow1, vhigh1) = cofactors(v1);
+        let (vlow2, vhigh2) = cofactors(v2);
+        let u = ZDD::new_node(
+            key - 2,
+            ZDD::apply_node(op, &vlow1, &vlow2),
+            ZDD::apply_node(op, &vhigh1, &vhigh2),
+        );
+        N::with_computed_table(|t| t.insert(hash_key, u.clone()));
+        u
+    }
+    /// return the sets in `node` which contain `var_index` if `value`, or which don't
+    /// otherwise. `var_index` is removed from them.
+    fn subset_node(node: &N, var_index: usize, value: bool) -> N {
+        let key = var_index + 2;
+        if node.is_constant().is_some() || key < node.unified_key() {
+            return if value {
+                N::new_constant(false)
+            } else {
+                node.clone()
+            };
+        }
+        if key == node.unified_key() {
+            return if value {
+                node.high().unwrap()
+            } else {
+                node.low().unwrap()
+            };
+        }
+        let f = N::new_constant(false);
+        let hash_key = (
+            Operation::ZddSubset(var_index, value),
+            node.clone(),
+            f.clone(),
+            f,
+        );
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let n = ZDD::new_node(
+            node.var_index().unwrap(),
+            ZDD::subset_node(&node.low().unwrap(), var_index, value),
+            ZDD::subset_node(&node.high().unwrap(), var_index, value),
+        );
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return the family made by toggling `var_index` in every set in `node`.
+    fn change_node(node: &N, var_index: usize) -> N {
+        let f = N::new_constant(false);
+        let key = var_index + 2;
+        if node.is_constant().is_some() || key < node.unified_key() {
+            return ZDD::new_node(var_index, f, node.clone());
+        }
+        if key == node.unified_key() {
+            return ZDD::new_node(var_index, node.high().unwrap(), node.low().unwrap());
+        }
+        let hash_key = (Operation::ZddChange(var_index), node.clone(), f.clone(), f);
+        if let Some(n) = N::with_computed_table(|t| t.get(&hash_key)) {
+            return n;
+        }
+        let n = ZDD::new_node(
+            node.var_index().unwrap(),
+            ZDD::change_node(&node.low().unwrap(), var_index),
+            ZDD::change_node(&node.high().unwrap(), var_index),
+        );
+        N::with_computed_table(|t| t.insert(hash_key, n.clone()));
+        n
+    }
+    /// return the node for `var_index` after applying the zero-suppression rule of ZDD.
+    fn new_node(var_index: usize, low: N, high: N) -> N {
+        if high.is_constant() == Some(false) {
+            low
+        } else {
+            N::new_var(var_index, low, high)
+        }
+    }
 }
 
 impl<N: DecisionDiagram<N> + DecisionDiagramNode> DecisionDiagram<N> for ZDD<N> {
-    fn all_nodes(&self) -> HashSet<&N> {
+    fn all_nodes(&self) -> HashSet<N> {
         self.graph.all_nodes()
     }
     fn len(&self) -> usize {
         self.graph.len()
     }
+    /// writes the nodes as read through `low` and `high`, since complement
+    /// edges mean nothing to a family of sets.
     fn write_as_gv(&self, sink: impl io::Write) -> io::Result<()> {
-        self.graph.write_as_gv(sink)
+        write_graph(&self.graph, sink)
     }
     fn satisfy_one(&self) -> bool {
         self.graph.satisfy_one()
@@ -50,165 +815,531 @@ impl<N: DecisionDiagram<N> + DecisionDiagramNode> DecisionDiagram<N> for ZDD<N>
     }
 }
 
-impl ReducedDecisionDiagram for ZDD<Node> {
+impl<N: DecisionDiagram<N> + DecisionDiagramNode> ReducedDecisionDiagram for ZDD<N> {
     fn reduce(&mut self) {
-        let root = &self.graph;
-        let (mut index, mut node) = Node::build_indexer(&[root.clone()]);
-        let mut vlist: HashMap<usize, Vec<&Node>> = HashMap::new();
-        // put each vertex u on list vlist[u.var_index]
-        for n in root.all_nodes().iter().cloned() {
-            vlist.entry(n.unified_key()).or_default().push(n);
-        }
-        let mut next_id: usize = 2;
-        for vi in vlist.keys().sorted().rev() {
-            let mut q: Vec<((usize, usize), &Node)> = Vec::new();
-            for node in vlist[vi].iter().cloned() {
-                match **node {
-                    Vertex::Bool(_) => (),
-                    Vertex::Var {
-                        ref low, ref high, ..
-                    } => {
-                        if index[high] == 0 {
-                            // redundant vertex
-                            index.insert(node.clone(), index[low]);
-                        } else {
-                            q.push(((index[low], index[high]), node));
-                        }
-                    }
-                }
-            }
-            q.sort_unstable_by_key(|(k, _)| *k);
-            let mut old_key: (usize, usize) = (usize::MAX, usize::MAX);
-            for (key, n) in q.iter().cloned() {
-                if key == old_key {
-                    index.insert(n.clone(), next_id);
-                } else {
-                    next_id += 1;
-                    match **n {
-                        Vertex::Bool(_) => {
-                            index.insert(n.clone(), next_id);
-                            node.insert(next_id, n.clone());
-                        }
-                        Vertex::Var {
-                            var_index,
-                            ref low,
-                            ref high,
-                        } => {
-                            let nn = Node::new_var(
-                                var_index,
-                                node[&index[low]].clone(),
-                                node[&index[high]].clone(),
-                            );
-                            index.insert(n.clone(), next_id);
-                            index.insert(nn.clone(), next_id);
-                            node.insert(next_id, nn);
-                        }
-                    }
-                    old_key = key;
-                }
-            }
+        self.graph = self.graph.rebuild(&ZDD::new_node);
+    }
+    /// return the family of the sets `s` such that `op(s in self, s in other)`.
+    /// Since a ZDD can't hold the sets in neither family, `op(false, false)`
+    /// must be `false`.
+    fn apply(&self, op: BinOp, other: &Self) -> ZDD<N> {
+        assert!(
+            !op.eval(false, false),
+            "{op:?} can't be applied to ZDDs, since it holds for the sets in neither family"
+        );
+        ZDD {
+            graph: ZDD::apply_node(op, &self.graph, &other.graph),
+            ..Default::default()
+        }
+    }
+    /// return the family of the sets `s` such that `s` with `at` set to `s in other`
+    /// is in self, as the composition of the characteristic functions, in which
+    /// a var absent from a family must be 0.
+    /// It's `(A1 & other) | (A0 - other)`, where `Ai` is the sets which become
+    /// a member of self by setting `at` to `i`.
+    fn compose(&self, other: &Self, at: usize) -> Self {
+        let expand = |n: N| {
+            let changed = ZDD::change_node(&n, at);
+            ZDD::apply_node(BinOp::Or, &n, &changed)
+        };
+        let a0 = expand(ZDD::subset_node(&self.graph, at, false));
+        let a1 = expand(ZDD::change_node(
+            &ZDD::subset_node(&self.graph, at, true),
+            at,
+        ));
+        let g = &other.graph;
+        ZDD {
+            graph: ZDD::apply_node(
+                BinOp::Or,
+                &ZDD::apply_node(BinOp::And, &a1, g),
+                &ZDD::apply_node(BinOp::Diff, &a0, g),
+            ),
+            ..Default::default()
         }
-        // pick up a tree from the hash-table
-        self.graph = node[&next_id].clone();
-    }
-    fn apply(&self, op: Box<dyn Fn(bool, bool) -> bool>, unit: bool, other: &Self) -> ZDD<Node> {
-        fn aux(
-            operator @ (op, unit): &BooleanOperator,
-            (v1, v2): (Node, Node),
-            indexer @ (index, node): &Indexer<Node>,
-            evaluation: &mut HashMap<Node, bool>,
-            merged: &mut HashMap<(usize, usize), Node>,
-        ) -> Node {
-            let hash_key = (index[&v1], index[&v2]);
-            if let Some(n) = merged.get(&hash_key) {
-                return n.clone(); // have already evaluaten
-            }
-            let value1 = evaluation.get(&v1);
-            let value2 = evaluation.get(&v2);
-            let value = match (value1, value2) {
-                (Some(a), _) if *a == *unit => Some(*unit),
-                (_, Some(b)) if *b == *unit => Some(*unit),
-                (None, _) | (_, None) => None,
-                (Some(a), Some(b)) => Some(op(*a, *b)),
-            };
-            if let Some(b) = value {
-                return node[&(b as usize)].clone();
-            }
-            let v1key = v1.unified_key();
-            let v2key = v2.unified_key();
-            let key = match (v1key < 2, v2key < 2) {
-                (false, false) => v1key.min(v2key),
-                (false, true) => v1key,
-                (true, false) => v2key,
-                (true, true) => op(v1key == 1, v2key == 1) as usize,
-            };
-            let u = if key < 2 {
-                Node::new_constant(key == 1)
-            } else {
-                let (vlow1, vhigh1) = if v1key == key {
-                    (v1.low().unwrap().clone(), v1.high().unwrap().clone())
-                } else {
-                    (v1.clone(), v1.clone())
-                };
-                let (vlow2, vhigh2) = if v2key == key {
-                    (v2.low().unwrap().clone(), v2.high().unwrap().clone())
-                } else {
-                    (v2.clone(), v2.clone())
-                };
-                Node::new_var(
-                    key - 2,
-                    aux(operator, (vlow1, vlow2), indexer, evaluation, merged),
-                    aux(operator, (vhigh1, vhigh2), indexer, evaluation, merged),
-                )
-            };
-            if let Some(b) = value {
-                evaluation.insert(u.clone(), b);
-            }
-            merged.insert(hash_key, u.clone());
-            u
-        }
-        // mapping from index pair to index
-        let mut merged: HashMap<(usize, usize), Node> = HashMap::new();
-        // mapping from node to bool
-        let mut evaluation: HashMap<Node, bool> = HashMap::new();
-        ZDD::new_from(aux(
-            &(op, unit),
-            (self.graph.clone(), other.graph.clone()),
-            &Node::build_indexer(&[self.graph.clone(), other.graph.clone()]),
-            &mut evaluation,
-            &mut merged,
-        ))
-    }
-    fn compose(&self, _other: &Self, _at: usize) -> Self {
-        unimplemented!()
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{
-        node::{example, Node},
-        types::{DecisionDiagram, DecisionDiagramNode},
-        zdd::ZDD,
+    use {
+        crate::{
+            count::{BigUint, Count},
+            node::{Node, example},
+            types::{BinOp, DecisionDiagram, DecisionDiagramNode, ReducedDecisionDiagram},
+            zdd::ZDD,
+        },
+        itertools::Itertools,
+        std::collections::HashMap,
     };
 
     #[test]
     fn test_satisfy_one() {
         let f = Node::new_constant(false);
-        assert_eq!(ZDD::new_from(f.clone()).satisfy_one(), false);
+        assert!(!ZDD::new_from(f.clone()).satisfy_one());
         let ff: Node = Node::new_var(2, f.clone(), f.clone());
         let zdd: ZDD<Node> = ZDD::new_from(ff);
-        assert_eq!(zdd.satisfy_one(), false);
+        assert!(!zdd.satisfy_one());
         let major = ZDD::new_from(example::majority());
-        assert_eq!(major.satisfy_one(), true);
+        assert!(major.satisfy_one());
+    }
+    #[test]
+    fn test_write_as_gv() {
+        for zdd in [
+            ZDD::new_from(example::independent_set()),
+            ZDD::from_sets([vec![1], vec![2, 3]]),
+            ZDD::base(),
+        ] {
+            let mut buffer: Vec<u8> = Vec::new();
+            zdd.write_as_gv(&mut buffer).unwrap();
+            let gv = String::from_utf8(buffer).unwrap();
+            assert!(!gv.contains("odot"));
+            assert_eq!(gv.matches("label=").count(), zdd.len());
+        }
     }
     #[test]
     fn test_satisfy_all() {
         let major = ZDD::new_from(example::majority());
-        assert_eq!(major.satisfy_one(), true);
+        assert!(major.satisfy_one());
         assert_eq!(major.satisfy_all(), 3);
         let ind = ZDD::new_from(example::independent_set());
-        assert_eq!(ind.satisfy_one(), true);
+        assert!(ind.satisfy_one());
         assert_eq!(ind.satisfy_all(), 18);
     }
+    #[test]
+    fn test_satisfy_one_set() {
+        assert_eq!(
+            ZDD::new_from(Node::new_constant(false)).satisfy_one_set(),
+            None
+        );
+        assert_eq!(
+            ZDD::new_from(Node::new_constant(true)).satisfy_one_set(),
+            Some(vec![])
+        );
+        let ind = ZDD::new_from(example::independent_set());
+        assert_eq!(ind.satisfy_one_set(), Some(vec![]));
+        let krn = ZDD::new_from(example::kernels());
+        let set = krn.satisfy_one_set().unwrap();
+        // a kernel is a maximal independent set of the 6-cycle
+        assert!(set.iter().all(|i| !set.contains(&(i % 6 + 1))));
+        assert!((1..=6).all(|i| set.contains(&i)
+            || set.contains(&(i % 6 + 1))
+            || set.contains(&((i + 4) % 6 + 1))));
+    }
+    #[test]
+    fn test_sets() {
+        assert_eq!(ZDD::new_from(Node::new_constant(false)).sets().count(), 0);
+        let ind = ZDD::new_from(example::independent_set());
+        let mut sets = ind.sets().collect::<Vec<_>>();
+        assert_eq!(sets.len(), 18);
+        assert!(sets.iter().all(|s| s.is_sorted()));
+        assert!(
+            sets.iter()
+                .all(|s| s.iter().all(|i| !s.contains(&(i % 6 + 1))))
+        );
+        sets.sort();
+        sets.dedup();
+        assert_eq!(sets.len(), 18);
+        assert_eq!(ind.sets().next(), Some(vec![]));
+    }
+    #[test]
+    fn test_rename() {
+        let ind = ZDD::new_from(example::independent_set());
+        let shifted = ind.permute(&[0, 11, 12, 13, 14, 15, 16]);
+        assert_eq!(shifted.len(), ind.len());
+        assert_eq!(
+            sorted(&shifted),
+            sorted(&ind)
+                .into_iter()
+                .map(|s| s.into_iter().map(|i| i + 10).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        );
+        // the independent sets of a cycle are symmetric
+        assert_eq!(ind.permute(&[0, 6, 5, 4, 3, 2, 1]), ind);
+        assert_eq!(ind.permute(&[0, 2, 3, 4, 5, 6, 1]), ind);
+        let krn = ZDD::new_from(example::kernels());
+        let moved = krn.permute(&[0, 4, 6, 1, 2, 3, 5]);
+        assert_eq!(
+            sorted(&moved),
+            sorted(&krn)
+                .into_iter()
+                .map(|s| s
+                    .into_iter()
+                    .map(|i| [0, 4, 6, 1, 2, 3, 5][i])
+                    .sorted()
+                    .collect::<Vec<_>>())
+                .sorted()
+                .collect::<Vec<_>>()
+        );
+        // merging elements
+        let major = ZDD::new_from(example::majority());
+        assert_eq!(
+            sorted(&major.rename(&HashMap::from([(1, 2)]))),
+            vec![vec![2], vec![2, 3]]
+        );
+    }
+    /// return the family of the subsets of `1..=4` which satisfy `f`
+    fn family(f: impl Fn(&[usize]) -> bool) -> Vec<Vec<usize>> {
+        all_subsets(4).filter(|s| f(s)).sorted().collect()
+    }
+    /// return the composition by definition: `s` is a member if `s` with `at`
+    /// set to `s in g` is a member of `f`.
+    fn compose_by_definition(f: &ZDD<Node>, g: &ZDD<Node>, at: usize) -> Vec<Vec<usize>> {
+        let f = f.sets().collect::<Vec<_>>();
+        let g = g.sets().collect::<Vec<_>>();
+        family(|s| {
+            let mut t = s.iter().copied().filter(|i| *i != at).collect::<Vec<_>>();
+            if g.iter().any(|m| m == s) {
+                t.push(at);
+                t.sort();
+            }
+            f.contains(&t)
+        })
+    }
+    #[test]
+    fn test_compose() {
+        let x1x2x4 = ZDD::new_from(example::x1x2x4());
+        let x2x3 = ZDD::new_from(example::x2x3());
+        let composed = x1x2x4.compose(&x2x3, 2);
+        assert_eq!(sorted(&composed), compose_by_definition(&x1x2x4, &x2x3, 2));
+        let diagrams = [
+            ZDD::new_from(Node::new_constant(false)),
+            ZDD::new_from(Node::new_constant(true)),
+            x1x2x4,
+            x2x3,
+            ZDD::new_from(example::majority()),
+            ZDD::new_from(example::x1x3()),
+        ];
+        for f in diagrams.iter() {
+            for g in diagrams.iter() {
+                for at in 1..=4 {
+                    assert_eq!(sorted(&f.compose(g, at)), compose_by_definition(f, g, at));
+                }
+            }
+        }
+    }
+    /// return the sets in `z` in the lexicographic order
+    fn sorted(z: &ZDD<Node>) -> Vec<Vec<usize>> {
+        z.sets().sorted().collect()
+    }
+    /// return whether `a` is a subset of `b`
+    fn subset(a: &[usize], b: &[usize]) -> bool {
+        a.iter().all(|i| b.contains(i))
+    }
+    /// return all the subsets of `1..=n`, in the order of their bitmasks
+    fn all_subsets(n: usize) -> impl Iterator<Item = Vec<usize>> {
+        (0..1usize << n).map(move |bits| (1..=n).filter(|i| bits >> (i - 1) & 1 == 1).collect())
+    }
+    /// return a family of subsets of `1..=5` chosen by a linear congruential generator
+    fn random_family(seed: u64) -> Vec<Vec<usize>> {
+        let mut x = seed;
+        all_subsets(5)
+            .filter(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                x >> 62 == 0
+            })
+            .collect()
+    }
+    #[test]
+    fn test_set_operations() {
+        let families = (0..8).map(random_family).collect::<Vec<_>>();
+        for p in families.iter() {
+            let zp: ZDD<Node> = ZDD::from_sets(p.clone());
+            assert_eq!(sorted(&zp), p.iter().cloned().sorted().collect::<Vec<_>>());
+            for q in families.iter() {
+                let zq = ZDD::from_sets(q.clone());
+                let union = p.iter().chain(q.iter()).cloned().sorted().dedup();
+                let intersection = p.iter().filter(|s| q.contains(s)).cloned().sorted();
+                let difference = p.iter().filter(|s| !q.contains(s)).cloned().sorted();
+                let symmetric = p
+                    .iter()
+                    .filter(|s| !q.contains(s))
+                    .chain(q.iter().filter(|s| !p.contains(s)))
+                    .cloned()
+                    .sorted();
+                assert_eq!(sorted(&zp.union(&zq)), union.collect::<Vec<_>>());
+                assert_eq!(
+                    sorted(&zp.intersection(&zq)),
+                    intersection.collect::<Vec<_>>()
+                );
+                assert_eq!(sorted(&zp.difference(&zq)), difference.collect::<Vec<_>>());
+                assert_eq!(
+                    sorted(&zp.symmetric_difference(&zq)),
+                    symmetric.collect::<Vec<_>>()
+                );
+                assert_eq!(zp.union(&zq), zq.union(&zp));
+            }
+        }
+        // the families of different supports
+        let base: ZDD<Node> = ZDD::from_sets([vec![]]);
+        let ind = ZDD::new_from(example::independent_set());
+        assert_eq!(ind.intersection(&base), base);
+        assert_eq!(ind.difference(&base).count_sets(), 17);
+        let major = ZDD::new_from(example::majority());
+        assert_eq!(major.union(&base).symmetric_difference(&major), base);
+    }
+    #[test]
+    fn test_unate_cube_set_algebra() {
+        let families = (0..6).map(random_family).collect::<Vec<_>>();
+        let union = |a: &[usize], b: &[usize]| {
+            a.iter()
+                .chain(b)
+                .copied()
+                .sorted()
+                .dedup()
+                .collect::<Vec<_>>()
+        };
+        for p in families.iter() {
+            let zp: ZDD<Node> = ZDD::from_sets(p.clone());
+            for q in families.iter() {
+                let zq = ZDD::from_sets(q.clone());
+                let pairs = p.iter().cartesian_product(q.iter());
+                let join = pairs.clone().map(|(a, b)| union(a, b));
+                let meet = pairs
+                    .clone()
+                    .map(|(a, b)| a.iter().filter(|i| b.contains(i)).copied().collect());
+                let disjoint = pairs
+                    .clone()
+                    .filter(|(a, b)| a.iter().all(|i| !b.contains(i)))
+                    .map(|(a, b)| union(a, b));
+                let normalize =
+                    |sets: Vec<Vec<usize>>| sets.into_iter().sorted().dedup().collect::<Vec<_>>();
+                assert_eq!(sorted(&zp.join(&zq)), normalize(join.collect()));
+                assert_eq!(sorted(&zp.meet(&zq)), normalize(meet.collect()));
+                assert_eq!(
+                    sorted(&zp.disjoint_product(&zq)),
+                    normalize(disjoint.collect())
+                );
+                if q.is_empty() {
+                    continue;
+                }
+                // the largest quotient by definition over the subsets of 1..=5
+                let quotient = all_subsets(5)
+                    .filter(|r| {
+                        q.iter()
+                            .all(|s| s.iter().all(|i| !r.contains(i)) && p.contains(&union(r, s)))
+                    })
+                    .collect::<Vec<_>>();
+                let zr = zp.divide(&zq);
+                assert_eq!(sorted(&zr), quotient);
+                assert_eq!(zp.remainder(&zq), zp.difference(&zr.join(&zq)));
+                assert_eq!(zr.join(&zq).union(&zp.remainder(&zq)), zp);
+            }
+        }
+    }
+    #[test]
+    #[should_panic]
+    fn test_divide_by_empty() {
+        let ind = ZDD::new_from(example::independent_set());
+        ind.divide(&ZDD::default());
+    }
+    #[test]
+    fn test_primitives() {
+        assert_eq!(ZDD::<Node>::empty().change(1), ZDD::empty());
+        assert_eq!(sorted(&ZDD::<Node>::base().change(1)), vec![vec![1]]);
+        for p in (0..8).map(random_family) {
+            let zp: ZDD<Node> = ZDD::from_sets(p.clone());
+            for vi in 1..=6 {
+                let onset = p
+                    .iter()
+                    .filter(|s| s.contains(&vi))
+                    .map(|s| s.iter().copied().filter(|i| *i != vi).collect::<Vec<_>>())
+                    .sorted()
+                    .collect::<Vec<_>>();
+                let offset = p
+                    .iter()
+                    .filter(|s| !s.contains(&vi))
+                    .cloned()
+                    .sorted()
+                    .collect::<Vec<_>>();
+                let change = p
+                    .iter()
+                    .map(|s| {
+                        if s.contains(&vi) {
+                            s.iter().copied().filter(|i| *i != vi).collect::<Vec<_>>()
+                        } else {
+                            s.iter().copied().chain([vi]).sorted().collect()
+                        }
+                    })
+                    .sorted()
+                    .collect::<Vec<_>>();
+                assert_eq!(sorted(&zp.onset(vi)), onset);
+                assert_eq!(sorted(&zp.offset(vi)), offset);
+                assert_eq!(sorted(&zp.change(vi)), change);
+                assert_eq!(zp.change(vi).change(vi), zp);
+                assert_eq!(zp.onset(vi).change(vi).union(&zp.offset(vi)), zp);
+            }
+        }
+        // build the independent sets of the 6-cycle incrementally: add `vi`
+        // to the sets which have none of its neighbors
+        let ind = (1..=6).fold(ZDD::<Node>::base(), |family, vi| {
+            let mut allowed = family.offset(vi - 1);
+            if vi == 6 {
+                allowed = allowed.offset(1);
+            }
+            family.union(&allowed.change(vi))
+        });
+        assert_eq!(ind, ZDD::new_from(example::independent_set()));
+    }
+    #[test]
+    fn test_extremal() {
+        let ind = ZDD::new_from(example::independent_set());
+        assert_eq!(ind.maximal(), ZDD::new_from(example::kernels()));
+        assert_eq!(ind.minimal(), ZDD::base());
+        let families = (0..6).map(random_family).collect::<Vec<_>>();
+        for p in families.iter() {
+            let zp: ZDD<Node> = ZDD::from_sets(p.clone());
+            let maximal = p
+                .iter()
+                .filter(|a| p.iter().all(|b| *a == b || !subset(a, b)))
+                .cloned()
+                .sorted()
+                .collect::<Vec<_>>();
+            let minimal = p
+                .iter()
+                .filter(|a| p.iter().all(|b| *a == b || !subset(b, a)))
+                .cloned()
+                .sorted()
+                .collect::<Vec<_>>();
+            assert_eq!(sorted(&zp.maximal()), maximal);
+            assert_eq!(sorted(&zp.minimal()), minimal);
+            for q in families.iter() {
+                let zq = ZDD::from_sets(q.clone());
+                let nonsup = p
+                    .iter()
+                    .filter(|a| q.iter().all(|b| !subset(b, a)))
+                    .cloned()
+                    .sorted()
+                    .collect::<Vec<_>>();
+                let nonsub = p
+                    .iter()
+                    .filter(|a| q.iter().all(|b| !subset(a, b)))
+                    .cloned()
+                    .sorted()
+                    .collect::<Vec<_>>();
+                assert_eq!(sorted(&zp.nonsup(&zq)), nonsup);
+                assert_eq!(sorted(&zp.nonsub(&zq)), nonsub);
+            }
+        }
+    }
+    #[test]
+    fn test_minimal_hitting_sets() {
+        let universe = all_subsets(5).collect::<Vec<_>>();
+        for seed in 0..8 {
+            let p = random_family(seed);
+            let zp: ZDD<Node> = ZDD::from_sets(p.clone());
+            let hitting = universe
+                .iter()
+                .filter(|h| p.iter().all(|s| s.iter().any(|i| h.contains(i))))
+                .collect::<Vec<_>>();
+            let minimal = hitting
+                .iter()
+                .filter(|h| hitting.iter().all(|g| g == *h || !subset(g, h)))
+                .map(|h| (*h).clone())
+                .sorted()
+                .collect::<Vec<_>>();
+            let transversal = zp.minimal_hitting_sets();
+            assert_eq!(sorted(&transversal), minimal);
+            // the transversal of the transversal is the minimal sets
+            assert_eq!(transversal.minimal_hitting_sets(), zp.minimal());
+        }
+        assert_eq!(ZDD::<Node>::empty().minimal_hitting_sets(), ZDD::base());
+        assert_eq!(ZDD::<Node>::base().minimal_hitting_sets(), ZDD::empty());
+        // the kernels are the independent sets which hit every closed neighborhood
+        let ind: ZDD<Node> = ZDD::new_from(example::independent_set());
+        let neighborhoods: ZDD<Node> =
+            ZDD::from_sets((1..=6).map(|i| vec![(i + 4) % 6 + 1, i, i % 6 + 1]));
+        let dominating = neighborhoods.minimal_hitting_sets();
+        let krn = ind.difference(&ind.nonsup(&dominating));
+        assert_eq!(krn, ZDD::new_from(example::kernels()));
+    }
+    #[test]
+    fn test_cardinality() {
+        for seed in 0..6 {
+            let p = random_family(seed);
+            let zp: ZDD<Node> = ZDD::from_sets(p.clone());
+            let filter = |f: &dyn Fn(usize) -> bool| {
+                p.iter()
+                    .filter(|s| f(s.len()))
+                    .cloned()
+                    .sorted()
+                    .collect::<Vec<_>>()
+            };
+            for k in 0..=6 {
+                assert_eq!(sorted(&zp.exactly(k)), filter(&|n| n == k));
+                assert_eq!(sorted(&zp.at_most(k)), filter(&|n| n <= k));
+                assert_eq!(sorted(&zp.at_least(k)), filter(&|n| k <= n));
+            }
+            let max = p.iter().map(|s| s.len() + 1).max().unwrap_or(0);
+            let distribution = (0..max)
+                .map(|k| p.iter().filter(|s| s.len() == k).count())
+                .collect::<Vec<_>>();
+            assert_eq!(zp.size_distribution(), distribution);
+        }
+        assert!(ZDD::<Node>::empty().size_distribution().is_empty());
+        assert_eq!(ZDD::<Node>::base().size_distribution(), vec![1]);
+        let big: ZDD<Node> = ZDD::k_subsets(0..100, 50);
+        let by_size = big.size_distribution_as::<BigUint>();
+        assert_eq!(by_size.len(), 51);
+        assert_eq!(by_size[50].to_string(), "100891344545564193334812497256");
+        for k in 0..=5 {
+            let subsets: ZDD<Node> = ZDD::k_subsets([3, 1, 5, 2, 4, 1], k);
+            let expected = (1..=5).combinations(k).collect::<Vec<_>>();
+            assert_eq!(sorted(&subsets), expected);
+        }
+        assert_eq!(ZDD::<Node>::k_subsets(1..=3, 4), ZDD::empty());
+        assert_eq!(ZDD::<Node>::k_subsets(1..=3, usize::MAX), ZDD::empty());
+        assert_eq!(ZDD::<Node>::k_subsets([2, 2, 1], 3), ZDD::empty());
+        assert_eq!(ZDD::<Node>::k_subsets([], 0), ZDD::base());
+    }
+    #[test]
+    fn test_count_sets() {
+        let ind = ZDD::new_from(example::independent_set());
+        assert_eq!(ind.count_sets(), 18);
+        assert_eq!(ind.count_sets_as::<BigUint>(), BigUint::from(18u64));
+        assert_eq!(ind.count_sets_as::<f64>(), 18.0);
+        // the power set of 130 elements
+        let power = (0..130).rev().fold(Node::new_constant(true), |n, vi| {
+            Node::new_var(vi, n.clone(), n)
+        });
+        let power = ZDD::new_from(power);
+        assert_eq!(power.len(), 131);
+        assert_eq!(power.count_sets_as::<BigUint>(), BigUint::one().shl(130));
+    }
+    #[test]
+    fn test_canonicity() {
+        let ind = ZDD::new_from(example::independent_set());
+        assert_eq!(ind, ZDD::new_from(example::independent_set()));
+        assert_eq!(ind.len(), 10);
+        let krn = ZDD::new_from(example::kernels());
+        assert_ne!(ind, krn);
+        assert_eq!(krn, krn.apply(BinOp::And, &krn));
+    }
+    #[test]
+    fn test_apply() {
+        let ind = ZDD::new_from(example::independent_set());
+        let krn = ZDD::new_from(example::kernels());
+        // every kernel is an independent set
+        assert_eq!(krn.apply(BinOp::And, &ind), krn);
+        assert_eq!(ind.apply(BinOp::Or, &krn), ind);
+        assert_eq!(krn.apply(BinOp::Diff, &ind), ZDD::default());
+        let rest = ind.apply(BinOp::Diff, &krn);
+        assert_eq!(rest.satisfy_all(), ind.satisfy_all() - krn.satisfy_all());
+        assert_eq!(rest.apply(BinOp::Xor, &krn), ind);
+        assert_eq!(krn.apply(BinOp::ConverseDiff, &ind), rest);
+        // the families of different supports
+        let major = ZDD::new_from(example::majority());
+        let both = major.apply(BinOp::And, &ind);
+        assert_eq!(
+            both.satisfy_all() + major.apply(BinOp::Or, &ind).satisfy_all(),
+            major.satisfy_all() + ind.satisfy_all()
+        );
+    }
+    #[test]
+    #[should_panic]
+    fn test_apply_unbounded() {
+        let ind = ZDD::new_from(example::independent_set());
+        ind.apply(BinOp::Nor, &ind);
+    }
 }